@@ -0,0 +1,882 @@
+// the timer itself: modes, laps, persistence and exports, nothing here knows about the terminal
+use std::{collections::VecDeque, fs, io, path::Path, time::{Duration, Instant}};
+
+use chrono::{DateTime, Local, NaiveTime, TimeDelta};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_COUNTDOWN: Duration = Duration::from_secs(5 * 60);
+const ALARM_BLINK: Duration = Duration::from_millis(500); // digit blink after a countdown finishes
+
+/// what the clock counts, countdown-like modes show the time left
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClockMode {
+    Stopwatch,
+    Countdown { target: Duration },
+    Pomodoro { work: Duration, rest: Duration }, // alternating work and break countdowns
+    Interval { work: Duration, rest: Duration, rounds: u32 }, // work and rest repeated, done after the last work phase
+    WallClock, // local time of day, start/pause and laps do nothing
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeFormat {
+    HmsMillis, // HH:MM:SS:mmm
+    Hms, // HH:MM:SS, hours hidden when zero
+    MsMillis, // MM:SS:mmm, minutes hidden when zero
+}
+
+impl TimeFormat {
+    fn next(self) -> Self {
+        match self {
+            TimeFormat::HmsMillis => TimeFormat::Hms,
+            TimeFormat::Hms => TimeFormat::MsMillis,
+            TimeFormat::MsMillis => TimeFormat::HmsMillis,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum Precision {
+    Millis, // :mmm
+    Centis, // .cc
+    Seconds, // no fraction
+}
+
+impl Precision {
+    fn next(self) -> Self {
+        match self {
+            Precision::Millis => Precision::Centis,
+            Precision::Centis => Precision::Seconds,
+            Precision::Seconds => Precision::Millis,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LapStats {
+    pub count: usize,
+    pub average: Duration,
+    pub fastest: Duration,
+    pub slowest: Duration,
+}
+
+// the JSON export, times are whole milliseconds so other tools don't need to know serde's Duration format
+#[derive(Debug, Serialize)]
+struct SessionExport {
+    elapsed_ms: u64,
+    elapsed_ns: u128,
+    running: bool,
+    laps: Vec<u64>, // cumulative time of each lap
+}
+
+/// one recorded lap or split
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lap {
+    pub at: Duration, // elapsed time when the lap was taken
+    #[serde(default)]
+    pub delta: Duration, // time since the previous lap, splits in between don't count
+    #[serde(default)]
+    pub split: bool, // marks the time without closing the lap
+    #[serde(default)]
+    pub taken_at: Option<DateTime<Local>>, // wall-clock time of the lap, missing in older saves
+    #[serde(default)]
+    pub auto: bool, // recorded by --lap-every rather than a key press
+    pub label: Option<String>,
+}
+
+/// a single stopwatch or timer with its laps;
+/// runtime-only fields are skipped, a loaded clock comes back paused with no alarm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clockwatch {
+    mode: ClockMode,
+    countdown_target: Duration, // target used when switching into countdown
+    pub format: TimeFormat, // how durations are printed
+    pub precision: Precision, // fraction shown after the seconds
+    #[serde(skip)]
+    keep_laps: Option<usize>, // --keep-laps cap, the oldest laps are dropped past it
+    finished: bool, // countdown reached zero
+    #[serde(skip)]
+    alarm_active: bool, // finished countdown not yet acknowledged
+    #[serde(skip)]
+    alarm_started: Option<Instant>, // when the alarm went off, drives the blink
+    #[serde(skip)]
+    alarm_blink_on: bool, // digits are currently drawn red
+    phase: Phase, // current pomodoro or interval phase, unused in other modes
+    pomodoros: u32, // completed work phases
+    #[serde(default)]
+    round: u32, // current interval round, from 1
+    base: Duration, // time committed by earlier running segments
+    #[serde(skip)]
+    started_at: Option<Instant>, // start of the current segment, None while paused
+    laps: VecDeque<Lap>,
+    #[serde(default)]
+    dropped_laps: usize, // laps evicted by keep_laps, so numbering carries on from the true count
+    #[serde(skip)]
+    unsaved: bool, // laps changed since the last export or save
+    #[serde(skip)]
+    auto_lap_seen: Option<Duration>, // elapsed time already checked for --lap-every boundaries
+    #[serde(skip)]
+    last_milestone: Option<u128>, // how many milestone intervals the elapsed time had passed last check
+}
+
+impl Clockwatch {
+    /// a paused clock at zero with no laps
+    pub fn new(mode: ClockMode) -> Self {
+        let countdown_target = match mode {
+            ClockMode::Countdown { target } => target,
+            ClockMode::Stopwatch | ClockMode::Pomodoro { .. } | ClockMode::Interval { .. } | ClockMode::WallClock => DEFAULT_COUNTDOWN,
+        };
+        Clockwatch { mode, countdown_target, format: TimeFormat::HmsMillis, precision: Precision::Millis, keep_laps: None, finished: false, alarm_active: false, alarm_started: None, alarm_blink_on: false, phase: Phase::Work, pomodoros: 0, round: 1, base: Duration::ZERO, started_at: None, laps: VecDeque::new(), dropped_laps: 0, unsaved: false, auto_lap_seen: None, last_milestone: None }
+    }
+
+    /// a stopwatch picking up from saved state, `dropped_laps` went before the first of `laps`
+    pub fn resume(elapsed: Duration, running: bool, laps: Vec<Lap>, dropped_laps: usize) -> Self {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.base = elapsed;
+        clock.laps = laps.into();
+        clock.dropped_laps = dropped_laps;
+        if running {
+            clock.start();
+        }
+        clock
+    }
+
+    /// only countdowns need ticking, elapsed time is derived from the start instant;
+    /// returns true when a countdown or pomodoro phase ran out on this tick
+    pub fn update(&mut self) -> bool {
+        match self.mode {
+            ClockMode::Countdown { target } if self.running() && self.elapsed() >= target => {
+                self.base = target;
+                self.started_at = None;
+                self.finished = true;
+                self.alarm_active = true;
+                self.alarm_started = Some(Instant::now());
+                self.alarm_blink_on = true;
+                true
+            }
+            ClockMode::Countdown { .. } => {
+                // blink phase follows wall-clock time so it doesn't depend on the frame rate
+                if let Some(started) = self.alarm_started
+                    && self.alarm_active {
+                    self.alarm_blink_on = (started.elapsed().as_millis() / ALARM_BLINK.as_millis()).is_multiple_of(2);
+                }
+                false
+            }
+            // a paused pomodoro set past its phase waits for a start instead of switching and starting itself
+            ClockMode::Pomodoro { .. } if self.running() => {
+                let target = self.phase_target();
+                let elapsed = self.elapsed();
+                if elapsed >= target {
+                    if self.phase == Phase::Work {
+                        self.pomodoros += 1;
+                    }
+                    self.phase = match self.phase {
+                        Phase::Work => Phase::Break,
+                        Phase::Break => Phase::Work,
+                    };
+                    // the next phase starts straight away, carrying over any overshoot
+                    self.base = elapsed - target;
+                    self.started_at = Some(Instant::now());
+                    return true;
+                }
+                false
+            }
+            ClockMode::Interval { rounds, .. } if self.running() => {
+                let target = self.phase_target();
+                let elapsed = self.elapsed();
+                if elapsed < target {
+                    return false;
+                }
+                if self.phase == Phase::Work && self.round >= rounds {
+                    // the last work phase ends the sequence, there's no trailing rest
+                    self.base = target;
+                    self.started_at = None;
+                    self.finished = true;
+                    return true;
+                }
+                if self.phase == Phase::Break {
+                    self.round += 1;
+                }
+                self.phase = match self.phase {
+                    Phase::Work => Phase::Break,
+                    Phase::Break => Phase::Work,
+                };
+                self.base = elapsed - target;
+                self.started_at = Some(Instant::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// length of the current countdown, zero for the stopwatch and wall clock
+    pub fn phase_target(&self) -> Duration {
+        match self.mode {
+            ClockMode::Stopwatch | ClockMode::WallClock => Duration::ZERO,
+            ClockMode::Countdown { target } => target,
+            ClockMode::Pomodoro { work, .. } | ClockMode::Interval { work, .. } if self.phase == Phase::Work => work,
+            ClockMode::Pomodoro { rest, .. } | ClockMode::Interval { rest, .. } => rest,
+        }
+    }
+
+    pub fn acknowledge_alarm(&mut self) {
+        self.alarm_active = false;
+        self.alarm_started = None;
+        self.alarm_blink_on = false;
+    }
+
+    // accessors, the state itself only changes through the methods below
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn pomodoros(&self) -> u32 {
+        self.pomodoros
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn alarm_active(&self) -> bool {
+        self.alarm_active
+    }
+
+    pub fn alarm_blink_on(&self) -> bool {
+        self.alarm_blink_on
+    }
+
+    /// every kept lap and split, oldest first
+    pub fn laps(&self) -> &VecDeque<Lap> {
+        &self.laps
+    }
+
+    /// the --keep-laps cap, if any
+    pub fn keep_laps(&self) -> Option<usize> {
+        self.keep_laps
+    }
+
+    /// caps the kept laps at `keep`, dropping the oldest straight away if there are already more
+    pub fn set_keep_laps(&mut self, keep: Option<usize>) {
+        self.keep_laps = keep;
+        self.trim_laps();
+    }
+
+    /// laps dropped off the front by keep_laps, the first kept lap is number `dropped_laps() + 1`
+    pub fn dropped_laps(&self) -> usize {
+        self.dropped_laps
+    }
+
+    /// laps that would be lost without an export or save
+    pub fn unsaved(&self) -> bool {
+        self.unsaved && !self.laps.is_empty()
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.unsaved = false;
+    }
+
+    pub fn running(&self) -> bool {
+        self.started_at.is_some()
+    }
+
+    /// measured against a single start instant so frame timing can't make it drift
+    pub fn elapsed(&self) -> Duration {
+        self.base + self.started_at.map_or(Duration::ZERO, |start| start.elapsed())
+    }
+
+    /// elapsed time in nanoseconds, the precision every export writes the total with; laps keep exact
+    /// Durations as well, only duration_into_text rounds down to milliseconds for display
+    pub fn total_nanos(&self) -> u128 {
+        self.elapsed().as_nanos()
+    }
+
+    /// time shown on the clock: elapsed for stopwatch, remaining for countdown, time since midnight for the wall clock
+    pub fn display_time(&self) -> Duration {
+        match self.mode {
+            ClockMode::Stopwatch => self.elapsed(),
+            ClockMode::Countdown { .. } | ClockMode::Pomodoro { .. } | ClockMode::Interval { .. } => self.phase_target().saturating_sub(self.elapsed()),
+            ClockMode::WallClock => (Local::now().time() - NaiveTime::MIN).to_std().unwrap_or_default(),
+        }
+    }
+
+    /// no-op when already running or in wall-clock mode
+    pub fn start(&mut self) {
+        if self.running() || self.mode == ClockMode::WallClock {
+            return;
+        }
+        // a finished countdown or interval sequence starts over, laps are kept
+        if self.finished {
+            self.base = Duration::ZERO;
+            self.finished = false;
+            self.phase = Phase::Work;
+            self.round = 1;
+            self.acknowledge_alarm();
+        }
+        self.started_at = Some(Instant::now());
+    }
+
+    /// folds the running segment into the base so elapsed stays put
+    pub fn pause(&mut self) {
+        if let Some(start) = self.started_at.take() {
+            self.base += start.elapsed();
+        }
+    }
+
+    /// starts a paused clock and pauses a running one
+    pub fn toggle(&mut self) {
+        if self.running() {
+            self.pause();
+        } else {
+            self.start();
+        }
+    }
+
+    /// closes the current lap, true when it's a new best
+    pub fn lap(&mut self) -> bool {
+        self.record(false)
+    }
+
+    /// marks the time without closing the current lap
+    pub fn split(&mut self) {
+        self.record(true);
+    }
+
+    // a lap restarts the delta, a split only marks the cumulative time;
+    // returns true when a lap beats every earlier one, never for the first
+    fn record(&mut self, split: bool) -> bool {
+        let at = self.elapsed();
+        let delta = self.current_lap_time();
+        let new_best = !split && self.best_lap().is_some_and(|best| delta < best);
+        self.push_lap(Lap { at, delta, split, taken_at: Some(Local::now()), auto: false, label: None });
+        self.unsaved = true;
+        new_best
+    }
+
+    /// records a lap at each multiple of `every` passed since the last call and returns how many;
+    /// a frame that skips several boundaries gets one lap per boundary, each at its exact time
+    pub fn record_auto_laps(&mut self, every: Duration) -> usize {
+        if every.is_zero() || !matches!(self.mode, ClockMode::Stopwatch | ClockMode::Countdown { .. }) {
+            return 0;
+        }
+        let elapsed = self.elapsed();
+        // the first call, or the clock going backward, just starts watching from here
+        let seen = self.auto_lap_seen.filter(|&seen| seen <= elapsed).unwrap_or(elapsed);
+        self.auto_lap_seen = Some(elapsed);
+
+        let every_ns = every.as_nanos();
+        let mut boundary = (seen.as_nanos() / every_ns + 1) * every_ns;
+        let mut recorded = 0;
+        while boundary <= elapsed.as_nanos() {
+            let at = Duration::from_nanos(boundary as u64);
+            let taken_at = Local::now() - TimeDelta::from_std(elapsed - at).unwrap_or_default();
+            self.push_lap(Lap { at, delta: at.saturating_sub(self.last_lap_at()), split: false, taken_at: Some(taken_at), auto: true, label: None });
+            boundary += every_ns;
+            recorded += 1;
+        }
+        self.unsaved |= recorded > 0;
+        recorded
+    }
+
+    // a full ring drops its oldest lap to make room
+    fn push_lap(&mut self, lap: Lap) {
+        self.laps.push_back(lap);
+        self.trim_laps();
+    }
+
+    fn trim_laps(&mut self) {
+        while self.keep_laps.is_some_and(|keep| self.laps.len() > keep) {
+            self.laps.pop_front();
+            self.dropped_laps += 1;
+        }
+    }
+
+    /// whether a multiple of `every` was passed since the last call; several in one go still count once,
+    /// and the first call or the clock going backward only catches up without reporting one
+    pub fn passed_milestone(&mut self, every: Duration) -> bool {
+        if every.is_zero() || self.mode == ClockMode::WallClock {
+            return false;
+        }
+        let milestone = self.elapsed().as_nanos() / every.as_nanos();
+        let passed = self.last_milestone.is_some_and(|last| milestone > last);
+        self.last_milestone = Some(milestone);
+        passed
+    }
+
+    /// shortest lap so far, splits don't count
+    pub fn best_lap(&self) -> Option<Duration> {
+        self.laps.iter().filter(|lap| !lap.split).map(|lap| lap.delta).min()
+    }
+
+    /// time since the last lap, or since zero before the first one
+    pub fn current_lap_time(&self) -> Duration {
+        self.elapsed().saturating_sub(self.last_lap_at())
+    }
+
+    // splits don't close a lap, so the current one runs from the last real lap
+    fn last_lap_at(&self) -> Duration {
+        self.laps.iter().rev().find(|lap| !lap.split).map_or(Duration::ZERO, |lap| lap.at)
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            ClockMode::Stopwatch => ClockMode::Countdown { target: self.countdown_target },
+            ClockMode::Countdown { .. } | ClockMode::Pomodoro { .. } | ClockMode::Interval { .. } | ClockMode::WallClock => ClockMode::Stopwatch,
+        };
+        // elapsed time means something different in the other mode, so start fresh
+        self.reset();
+    }
+
+    /// delta of each entry from the lap before it, the first lap splits from zero
+    pub fn splits(&self) -> Vec<Duration> {
+        self.laps.iter().map(|lap| lap.delta).collect()
+    }
+
+    /// writes the full clock state as JSON, a running segment is folded into the saved time
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let mut snapshot = self.clone();
+        snapshot.base = self.elapsed();
+        snapshot.started_at = None;
+        fs::write(path, serde_json::to_string_pretty(&snapshot)?)
+    }
+
+    pub fn load_from(path: &Path) -> io::Result<Clockwatch> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// every lap as `lap_number,absolute_time,split_delta,absolute_ns,split_ns,wall_time`;
+    /// the text columns are rounded to millis like the display, the ns columns are exact, and
+    /// wall_time is the RFC 3339 time the lap was taken, empty for laps from older saves;
+    /// a last `total` row holds the elapsed time and the current lap time, which runs from the last lap, not split
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("lap_number,absolute_time,split_delta,absolute_ns,split_ns,wall_time\n");
+        for (i, (lap, split)) in self.laps.iter().zip(self.splits()).enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                self.dropped_laps + i + 1,
+                Clockwatch::duration_into_text(lap.at, TimeFormat::HmsMillis, Precision::Millis),
+                Clockwatch::duration_into_text(split, TimeFormat::HmsMillis, Precision::Millis),
+                lap.at.as_nanos(),
+                split.as_nanos(),
+                lap.taken_at.map_or(String::new(), |taken_at| taken_at.to_rfc3339()),
+            ));
+        }
+        let total = self.total_nanos();
+        let since_lap = total.saturating_sub(self.last_lap_at().as_nanos());
+        csv.push_str(&format!(
+            "total,{},{},{total},{since_lap},\n",
+            Clockwatch::duration_into_text(Duration::from_nanos(total as u64), TimeFormat::HmsMillis, Precision::Millis),
+            Clockwatch::duration_into_text(Duration::from_nanos(since_lap as u64), TimeFormat::HmsMillis, Precision::Millis),
+        ));
+        csv
+    }
+
+    pub fn export_csv(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    /// `{ elapsed_ms, elapsed_ns, running, laps: [ms...] }` for other tools to read
+    pub fn to_json(&self) -> String {
+        let total = self.total_nanos();
+        let export = SessionExport {
+            elapsed_ms: (total / 1_000_000) as u64,
+            elapsed_ns: total,
+            running: self.running(),
+            laps: self.laps.iter().map(|lap| lap.at.as_millis() as u64).collect(),
+        };
+        serde_json::to_string_pretty(&export).expect("plain numbers always serialize")
+    }
+
+    /// writes every lap as `lap_number,cumulative_ms,split_ms`, the header is always written
+    pub fn export_laps_csv(&self, path: &Path) -> io::Result<()> {
+        let mut csv = String::from("lap_number,cumulative_ms,split_ms\n");
+        for (i, (lap, split)) in self.laps.iter().zip(self.splits()).enumerate() {
+            csv.push_str(&format!("{},{},{}\n", self.dropped_laps + i + 1, lap.at.as_millis(), split.as_millis()));
+        }
+        fs::write(path, csv)
+    }
+
+    /// only laps count since splits don't close one, everything is zero when there are no laps
+    pub fn lap_stats(&self) -> LapStats {
+        // one pass without collecting, the summary asks for this every frame
+        let (count, sum, fastest, slowest) = self.laps.iter().filter(|lap| !lap.split).fold(
+            (0, Duration::ZERO, Duration::MAX, Duration::ZERO),
+            |(count, sum, fastest, slowest), lap| (count + 1, sum + lap.delta, fastest.min(lap.delta), slowest.max(lap.delta)),
+        );
+        LapStats {
+            count,
+            average: if count == 0 { Duration::ZERO } else { sum / count as u32 },
+            fastest: if count == 0 { Duration::ZERO } else { fastest },
+            slowest,
+        }
+    }
+
+    /// drops the most recent lap, elapsed time and running state are untouched
+    pub fn undo_lap(&mut self) -> Option<Duration> {
+        let undone = self.laps.pop_back().map(|lap| lap.at);
+        self.unsaved |= undone.is_some();
+        undone
+    }
+
+    /// removes any lap or split by index; the laps after a removed lap up to the next one now count from the lap before it
+    pub fn delete_lap(&mut self, index: usize) -> Option<Lap> {
+        let removed = self.laps.remove(index)?;
+        if !removed.split {
+            let start = removed.at.saturating_sub(removed.delta);
+            for lap in self.laps.range_mut(index..) {
+                lap.delta = lap.at.saturating_sub(start);
+                if !lap.split {
+                    break;
+                }
+            }
+        }
+        self.unsaved = true;
+        Some(removed)
+    }
+
+    /// a running clock keeps counting from the new time
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.base = elapsed;
+        self.auto_lap_seen = None;
+        if self.running() {
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    /// nudges a paused clock, going backward stops at zero
+    pub fn adjust(&mut self, delta: Duration, forward: bool) {
+        if self.running() {
+            return;
+        }
+        self.base = if forward { self.base + delta } else { self.base.saturating_sub(delta) };
+        self.auto_lap_seen = None;
+    }
+
+    /// checkpoint restore: a paused clock goes back to the most recent lap or split and returns it;
+    /// laps are left intact, the ones after the restore point just end up ahead of the clock
+    pub fn rewind_to_last_lap(&mut self) -> Option<Duration> {
+        if self.running() {
+            return None;
+        }
+        let at = self.laps.back()?.at;
+        self.base = at;
+        Some(at)
+    }
+
+    /// an empty label clears it
+    pub fn label_last_lap(&mut self, label: String) {
+        if let Some(lap) = self.laps.back_mut() {
+            lap.label = Some(label).filter(|label| !label.is_empty());
+            self.unsaved = true;
+        }
+    }
+
+    /// switches to a countdown of `target`, starting over
+    pub fn set_countdown(&mut self, target: Duration) {
+        self.countdown_target = target;
+        self.mode = ClockMode::Countdown { target };
+        self.reset();
+    }
+
+    pub fn clear_laps(&mut self) {
+        self.laps.clear();
+        self.dropped_laps = 0;
+    }
+
+    /// back to zero with no laps, resetting also stops the clock so it doesn't keep counting from zero
+    pub fn reset(&mut self) {
+        self.base = Duration::ZERO;
+        self.started_at = None;
+        self.laps.clear();
+        self.dropped_laps = 0;
+        self.finished = false;
+        self.acknowledge_alarm();
+        self.phase = Phase::Work;
+        self.pomodoros = 0;
+        self.round = 1;
+    }
+
+    pub fn cycle_format(&mut self) {
+        self.format = self.format.next();
+    }
+
+    pub fn cycle_precision(&mut self) {
+        self.precision = self.precision.next();
+    }
+
+    /// the format picks the leading fields, the precision picks the trailing fraction
+    pub fn duration_into_text(dt: Duration, format: TimeFormat, precision: Precision) -> String {
+        let all_millis = dt.as_millis();
+        let hours: u128 = all_millis / 1000 / 60 / 60;
+        let minutes: u128 = all_millis / 1000 / 60 % 60;
+        let secs: u128 = all_millis / 1000 % 60;
+        let millis: u128 = all_millis % 1000;
+        let fraction = match precision {
+            Precision::Millis => format!(":{:03}", millis),
+            Precision::Centis => format!(".{:02}", millis / 10),
+            Precision::Seconds => String::new(),
+        };
+        match format {
+            TimeFormat::HmsMillis => format!("{:02}:{:02}:{:02}{}", hours, minutes, secs, fraction),
+            TimeFormat::Hms if hours == 0 => format!("{:02}:{:02}", minutes, secs),
+            TimeFormat::Hms => format!("{:02}:{:02}:{:02}", hours, minutes, secs),
+            TimeFormat::MsMillis if hours == 0 && minutes == 0 => format!("{:02}{}", secs, fraction),
+            TimeFormat::MsMillis => format!("{:02}:{:02}{}", hours * 60 + minutes, secs, fraction),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn laps_keep_sub_millisecond_time() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.base = Duration::from_nanos(1_234_567_891);
+        clock.record(false);
+        assert_eq!(clock.laps[0].at, Duration::from_nanos(1_234_567_891));
+        assert_eq!(clock.total_nanos(), 1_234_567_891);
+        assert_eq!(Clockwatch::duration_into_text(clock.elapsed(), TimeFormat::HmsMillis, Precision::Millis), "00:00:01:234");
+    }
+
+    #[test]
+    fn csv_export_carries_the_wall_clock_time() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.base = Duration::from_millis(1500);
+        clock.lap();
+        clock.lap();
+        clock.laps[1].taken_at = None;
+        let csv = clock.to_csv();
+        let rows = csv.lines().collect::<Vec<_>>();
+        assert_eq!(rows[0], "lap_number,absolute_time,split_delta,absolute_ns,split_ns,wall_time");
+        let taken_at = clock.laps[0].taken_at.unwrap().to_rfc3339();
+        assert_eq!(rows[1], format!("1,00:00:01:500,00:00:01:500,1500000000,1500000000,{taken_at}"));
+        // a lap from an older save just leaves the column empty
+        assert_eq!(rows[2], "2,00:00:01:500,00:00:00:000,1500000000,0,");
+        clock.base = Duration::from_nanos(2_000_000_001);
+        assert_eq!(clock.to_csv().lines().last().unwrap(), "total,00:00:02:000,00:00:00:500,2000000001,500000001,");
+    }
+
+    #[test]
+    fn lap_stats_skip_splits() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        assert_eq!(clock.lap_stats(), LapStats { count: 0, average: Duration::ZERO, fastest: Duration::ZERO, slowest: Duration::ZERO });
+        for (secs, split) in [(2, false), (3, true), (7, false)] {
+            clock.base = Duration::from_secs(secs);
+            clock.record(split);
+        }
+        assert_eq!(clock.lap_stats(), LapStats { count: 2, average: Duration::from_millis(3500), fastest: Duration::from_secs(2), slowest: Duration::from_secs(5) });
+    }
+
+    #[test]
+    fn csv_total_counts_the_current_lap_past_a_split() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.base = Duration::from_secs(5);
+        clock.lap();
+        clock.base = Duration::from_secs(8);
+        clock.split();
+        clock.base = Duration::from_secs(10);
+        assert_eq!(clock.current_lap_time(), Duration::from_secs(5));
+        assert_eq!(clock.to_csv().lines().last().unwrap(), "total,00:00:10:000,00:00:05:000,10000000000,5000000000,");
+    }
+
+    #[test]
+    fn json_export_matches_fixture() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.base = Duration::from_millis(500);
+        clock.record(false);
+        clock.base = Duration::from_millis(1250);
+        clock.record(true);
+        clock.base = Duration::from_millis(3001);
+        let expected = r#"{
+  "elapsed_ms": 3001,
+  "elapsed_ns": 3001000000,
+  "running": false,
+  "laps": [
+    500,
+    1250
+  ]
+}"#;
+        assert_eq!(clock.to_json(), expected);
+    }
+
+    fn text(dt: Duration) -> String {
+        Clockwatch::duration_into_text(dt, TimeFormat::HmsMillis, Precision::Millis)
+    }
+
+    #[test]
+    fn duration_text_pads_every_field() {
+        assert_eq!(text(Duration::ZERO), "00:00:00:000");
+        assert_eq!(text(Duration::from_secs(1)), "00:00:01:000");
+        assert_eq!(text(Duration::from_secs(60)), "00:01:00:000");
+        assert_eq!(text(Duration::from_secs(3600)), "01:00:00:000");
+        assert_eq!(text(Duration::from_millis(((99 * 60 + 59) * 60 + 59) * 1000 + 999)), "99:59:59:999");
+    }
+
+    #[test]
+    fn duration_text_widens_past_99_hours() {
+        assert_eq!(text(Duration::from_secs(100 * 3600)), "100:00:00:000");
+        assert_eq!(text(Duration::from_secs(1234 * 3600 + 5)), "1234:00:05:000");
+        // the other fields keep their width so columns of mixed times still line up on the right
+        assert_eq!(text(Duration::from_secs(100 * 3600)).len(), text(Duration::from_secs(99 * 3600)).len() + 1);
+    }
+
+    #[test]
+    fn duration_text_follows_format_and_precision() {
+        let dt = Duration::from_millis(3_723_456);
+        assert_eq!(Clockwatch::duration_into_text(dt, TimeFormat::Hms, Precision::Millis), "01:02:03");
+        assert_eq!(Clockwatch::duration_into_text(dt, TimeFormat::MsMillis, Precision::Centis), "62:03.45");
+        assert_eq!(Clockwatch::duration_into_text(Duration::from_millis(5_120), TimeFormat::MsMillis, Precision::Seconds), "05");
+    }
+
+    #[test]
+    fn only_a_lap_beating_the_earlier_ones_is_fastest() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.base = Duration::from_secs(5);
+        assert!(!clock.record(false));
+        clock.base = Duration::from_secs(11);
+        assert!(!clock.record(false));
+        clock.base = Duration::from_secs(13);
+        assert!(!clock.record(true));
+        clock.base = Duration::from_secs(15);
+        assert!(clock.record(false));
+    }
+
+    #[test]
+    fn rewind_goes_back_to_the_last_lap_only_while_paused() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        assert_eq!(clock.rewind_to_last_lap(), None);
+        clock.base = Duration::from_secs(5);
+        clock.lap();
+        clock.base = Duration::from_secs(9);
+        assert_eq!(clock.rewind_to_last_lap(), Some(Duration::from_secs(5)));
+        assert_eq!(clock.elapsed(), Duration::from_secs(5));
+        assert_eq!(clock.laps().len(), 1);
+
+        clock.start();
+        assert_eq!(clock.rewind_to_last_lap(), None);
+    }
+
+    #[test]
+    fn auto_laps_cover_every_crossed_boundary() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        let every = Duration::from_secs(10);
+        assert_eq!(clock.record_auto_laps(every), 0);
+        // one long frame skips three boundaries
+        clock.base = Duration::from_secs(35);
+        assert_eq!(clock.record_auto_laps(every), 3);
+        assert_eq!(clock.laps().iter().map(|lap| (lap.at.as_secs(), lap.delta.as_secs(), lap.auto)).collect::<Vec<_>>(), [(10, 10, true), (20, 10, true), (30, 10, true)]);
+        clock.base = Duration::from_secs(39);
+        assert_eq!(clock.record_auto_laps(every), 0);
+
+        // jumping the clock doesn't backfill the laps in between
+        clock.set_elapsed(Duration::from_secs(95));
+        assert_eq!(clock.record_auto_laps(every), 0);
+        assert_eq!(clock.laps().len(), 3);
+    }
+
+    #[test]
+    fn keep_laps_drops_the_oldest_once_full() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.keep_laps = Some(3);
+        for secs in 1..=3 {
+            clock.base = Duration::from_secs(secs);
+            clock.lap();
+        }
+        // exactly full, nothing dropped yet
+        assert_eq!(clock.laps().len(), 3);
+        assert_eq!(clock.dropped_laps(), 0);
+
+        clock.base = Duration::from_secs(4);
+        clock.split();
+        assert_eq!(clock.laps().iter().map(|lap| lap.at.as_secs()).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(clock.dropped_laps(), 1);
+        assert!(clock.to_csv().lines().nth(1).unwrap().starts_with("2,"));
+
+        // lowering the cap trims what is already there
+        clock.set_keep_laps(Some(1));
+        assert_eq!(clock.laps().iter().map(|lap| lap.at.as_secs()).collect::<Vec<_>>(), [4]);
+        assert_eq!(clock.dropped_laps(), 3);
+
+        clock.reset();
+        assert_eq!(clock.dropped_laps(), 0);
+    }
+
+    #[test]
+    fn deleting_a_lap_merges_it_into_the_next() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        for (secs, split) in [(2, false), (5, false), (6, true), (9, false), (10, false)] {
+            clock.base = Duration::from_secs(secs);
+            if split { clock.split(); } else { clock.lap(); }
+        }
+        assert_eq!(clock.delete_lap(1).map(|lap| lap.at), Some(Duration::from_secs(5)));
+        let deltas = clock.laps().iter().map(|lap| lap.delta.as_secs()).collect::<Vec<_>>();
+        assert_eq!(deltas, [2, 4, 7, 1]);
+        // a split never closed a lap so nothing else changes
+        clock.delete_lap(1);
+        assert_eq!(clock.laps().iter().map(|lap| lap.delta.as_secs()).collect::<Vec<_>>(), [2, 7, 1]);
+        assert_eq!(clock.delete_lap(3), None);
+    }
+
+    #[test]
+    fn milestones_report_once_per_crossing() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        let every = Duration::from_secs(60);
+        assert!(!clock.passed_milestone(every));
+        clock.base = Duration::from_secs(59);
+        assert!(!clock.passed_milestone(every));
+        clock.base = Duration::from_secs(60);
+        assert!(clock.passed_milestone(every));
+        assert!(!clock.passed_milestone(every));
+        // three boundaries in one frame are still one milestone
+        clock.base = Duration::from_secs(250);
+        assert!(clock.passed_milestone(every));
+        clock.reset();
+        assert!(!clock.passed_milestone(every));
+    }
+
+    #[test]
+    fn a_paused_pomodoro_past_its_phase_stays_paused() {
+        let mut clock = Clockwatch::new(ClockMode::Pomodoro { work: Duration::from_secs(60), rest: Duration::from_secs(30) });
+        clock.set_elapsed(Duration::from_secs(120));
+        assert!(!clock.update());
+        assert!(!clock.running());
+        assert_eq!(clock.phase(), Phase::Work);
+
+        clock.start();
+        assert!(clock.update());
+        assert_eq!(clock.phase(), Phase::Break);
+    }
+
+    #[test]
+    fn interval_rounds_alternate_and_finish_after_the_last_work() {
+        let mut clock = Clockwatch::new(ClockMode::Interval { work: Duration::from_secs(20), rest: Duration::from_secs(10), rounds: 2 });
+        clock.toggle();
+        let mut step = |secs| {
+            clock.base = Duration::from_secs(secs);
+            clock.started_at = Some(Instant::now());
+            let switched = clock.update();
+            (switched, clock.phase, clock.round, clock.finished)
+        };
+        assert_eq!(step(5), (false, Phase::Work, 1, false));
+        assert_eq!(step(20), (true, Phase::Break, 1, false));
+        assert_eq!(step(10), (true, Phase::Work, 2, false));
+        assert_eq!(step(20), (true, Phase::Work, 2, true));
+        assert!(!clock.running());
+    }
+}
@@ -0,0 +1,39 @@
+// remote control through a named pipe: lines written to the FIFO arrive on a channel the ui drains between frames
+use std::{fs::{self, File}, io::{self, BufRead, BufReader}, path::Path, sync::mpsc::{self, Receiver}, thread};
+
+/// starts reading lines from the FIFO at `path`; opening it waits for a writer, so that happens on
+/// its own thread and the render loop never blocks; each writer that closes is followed by the next one
+pub fn listen(path: &Path) -> io::Result<Receiver<String>> {
+    check_fifo(path)?;
+    let path = path.to_path_buf();
+    let (lines, commands) = mpsc::channel();
+    thread::spawn(move || forward(&path, &lines));
+    Ok(commands)
+}
+
+// a regular file would be read to the end over and over, so only a FIFO is accepted
+#[cfg(unix)]
+fn check_fifo(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    if fs::metadata(path)?.file_type().is_fifo() {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a FIFO, create one with mkfifo", path.display())))
+    }
+}
+
+#[cfg(not(unix))]
+fn check_fifo(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "named pipes are only supported on unix"))
+}
+
+// stops once the pipe can't be opened any more or the app has gone
+fn forward(path: &Path, lines: &mpsc::Sender<String>) {
+    while let Ok(file) = File::open(path) {
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if lines.send(line).is_err() {
+                return;
+            }
+        }
+    }
+}
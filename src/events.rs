@@ -0,0 +1,148 @@
+// headless mode for scripts: commands come in on stdin one per line, events go out on stdout as JSON lines
+use std::{io::{self, BufRead, Write}, sync::mpsc::{self, RecvTimeoutError}, thread, time::Duration};
+
+use serde::Serialize;
+
+use crate::clockwatch::{ClockMode, Clockwatch};
+
+/// one line of output, e.g. `{"event":"lap","elapsed_ms":12340}`
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Event {
+    pub event: &'static str,
+    pub elapsed_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>, // only on errors
+}
+
+impl Event {
+    fn new(event: &'static str, elapsed: Duration) -> Self {
+        Event { event, elapsed_ms: elapsed.as_millis() as u64, message: None }
+    }
+}
+
+/// runs `clock` until stdin says `quit` or closes, checking for a finished countdown every `tick`
+pub fn run(mut clock: Clockwatch, tick: Duration) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    // an autostarted clock is already going, scripts still get to see it start
+    if clock.running() {
+        emit(&mut out, &Event::new("start", clock.elapsed()))?;
+    }
+
+    // stdin blocks, so lines are read on their own thread and the loop keeps ticking between them
+    let (lines, commands) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if lines.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        match commands.recv_timeout(tick) {
+            Ok(line) if line.trim() == "quit" => return Ok(()),
+            Ok(line) => {
+                let event = apply(&mut clock, &line).unwrap_or_else(|message| Event { message: Some(message), ..Event::new("error", clock.elapsed()) });
+                // a command that changed nothing, like pausing a paused clock, stays quiet
+                if event.event != "none" {
+                    emit(&mut out, &event)?;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        if clock.update() && clock.finished() {
+            emit(&mut out, &Event::new("finish", clock.elapsed()))?;
+        }
+    }
+}
+
+/// runs one stdin command: start, pause, toggle, lap, split or reset
+pub fn apply(clock: &mut Clockwatch, line: &str) -> Result<Event, String> {
+    let was_running = clock.running();
+    let event = match line.trim() {
+        "start" => {
+            clock.start();
+            if !was_running && clock.running() { "start" } else { "none" }
+        }
+        "pause" => {
+            clock.pause();
+            if was_running { "pause" } else { "none" }
+        }
+        "toggle" => {
+            clock.toggle();
+            match (was_running, clock.running()) {
+                (false, true) => "start",
+                (true, false) => "pause",
+                _ => "none",
+            }
+        }
+        // the wall clock has no laps, same as in the ui
+        "lap" | "split" if clock.mode() == ClockMode::WallClock => "none",
+        "lap" => {
+            clock.lap();
+            "lap"
+        }
+        "split" => {
+            clock.split();
+            "split"
+        }
+        "reset" => {
+            clock.reset();
+            "reset"
+        }
+        "" => "none",
+        other => return Err(format!("unknown command: {other}")),
+    };
+    // laps report the time they recorded rather than whatever has passed since
+    let elapsed = match event {
+        "lap" | "split" => clock.laps().back().map_or(clock.elapsed(), |lap| lap.at),
+        _ => clock.elapsed(),
+    };
+    Ok(Event::new(event, elapsed))
+}
+
+// one event per line, flushed straight away so a reader on the other end of a pipe sees it immediately
+fn emit(out: &mut impl Write, event: &Event) -> io::Result<()> {
+    writeln!(out, "{}", serde_json::to_string(event)?)?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_become_json_lines() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.set_elapsed(Duration::from_millis(12_340));
+        let mut out = Vec::new();
+        for line in ["lap", "pause", "start", "start", "reset"] {
+            let event = apply(&mut clock, line).unwrap();
+            if event.event != "none" {
+                emit(&mut out, &event).unwrap();
+            }
+        }
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], r#"{"event":"lap","elapsed_ms":12340}"#);
+        assert!(lines[1].starts_with(r#"{"event":"start","elapsed_ms":1234"#));
+        assert_eq!(lines[2], r#"{"event":"reset","elapsed_ms":0}"#);
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn unknown_commands_are_errors() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        assert_eq!(apply(&mut clock, "jump"), Err("unknown command: jump".to_string()));
+    }
+
+    #[test]
+    fn the_wall_clock_takes_no_laps() {
+        let mut clock = Clockwatch::new(ClockMode::WallClock);
+        for line in ["lap", "split"] {
+            assert_eq!(apply(&mut clock, line).unwrap().event, "none");
+        }
+        assert!(clock.laps().is_empty());
+    }
+}
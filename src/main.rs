@@ -1,163 +1,2862 @@
-use std::{io, time::{self, Duration, Instant}};
+use std::{env, fs, io, thread, path::{Path, PathBuf}, sync::mpsc::Receiver, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
 
-use color_eyre::owo_colors::OwoColorize;
-use ratatui::{DefaultTerminal, Frame, crossterm::event::{self, KeyCode, KeyEvent, KeyEventKind}, layout::{Constraint, Direction, Layout, Rect}, style::Stylize, text::{Line, Text, ToText}, widgets::{Block, Borders, Paragraph, Widget}};
+use arboard::Clipboard;
+use chrono::Local;
+use clap::{Parser, ValueEnum};
+use color_eyre::eyre::WrapErr;
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+
+use clockwatch::{ClockMode, Clockwatch, Lap, Phase, Precision, TimeFormat};
+use ratatui::{DefaultTerminal, Frame, crossterm::{event::{self, DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind}, execute, style::Print, terminal::SetTitle}, buffer::Buffer, layout::{Constraint, Direction, Layout, Margin, Position, Rect}, style::{Color, Style, Styled, Stylize}, text::{Line, Text}, widgets::{Block, Borders, Clear, Gauge, Paragraph, StatefulWidget, Tabs, Widget}};
+
+mod clockwatch;
+mod control;
+mod events;
 
 fn main() -> color_eyre::Result<()> {
     color_eyre::install()?;
 
+    // clap prints usage and exits non-zero on bad input, before the terminal is touched
+    let args = Cli::parse();
+    let mode = args.mode();
+
+    // a countdown from the command line always starts a fresh session
+    let mut clocks = if args.no_restore || args.emit_events || mode != ClockMode::Stopwatch {
+        vec![Clockwatch::new(mode)]
+    } else {
+        load_session().ok().filter(|clocks| !clocks.is_empty()).unwrap_or_else(|| vec![Clockwatch::new(mode)])
+    };
+    if let Some(elapsed) = args.start_at {
+        clocks[0].set_elapsed(elapsed);
+    }
+    for clock in &mut clocks {
+        clock.precision = args.precision;
+        clock.set_keep_laps(args.keep_laps.map(|keep| keep as usize));
+    }
+    // only the clock that opens active, restored tabs stay as they were saved;
+    // --start-at has already been applied, so it runs on from there
+    if args.autostart {
+        clocks[0].start();
+    }
+
+    // scripts drive a single clock over stdin and stdout, no terminal ui at all
+    if args.emit_events {
+        return Ok(events::run(clocks.remove(0), Duration::from_secs(1) / args.fps)?);
+    }
+
+    // checked before the terminal is taken over so a bad path is a plain error message
+    let control = args.control_fifo.as_deref().map(control::listen).transpose().wrap_err("couldn't use the control FIFO")?;
+
     let mut terminal = ratatui::init();
-    let mut app = App { clock: Clockwatch { elapsed_time: Duration::ZERO, running: false, laps: vec![] }, exit: false, last_frame: Instant::now() };
+    let guard = TerminalGuard { title: !args.no_title };
+    execute!(io::stdout(), EnableMouseCapture).wrap_err("couldn't enable mouse capture")?;
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    // the flags pick the preset, colors set in the file still go on top of it
+    let mut theme_config = ThemeConfig::load();
+    if let Some(name) = args.theme_name(no_color) {
+        theme_config.name = name;
+    }
+    let mut app = App::new(&args, clocks, theme_config, Keybinds::load(), control);
     let app_result = app.run(&mut terminal);
+    let saved = app.save_session();
 
-    ratatui::restore();
+    // restored before the report so it doesn't land on the alternate screen
+    drop(guard);
+    // a session that couldn't be saved is only a warning, it doesn't hold back the --run-for report
+    if let Err(err) = saved {
+        eprintln!("couldn't save the session: {err}");
+    }
+    app_result.wrap_err("the terminal stopped responding")?;
 
-    app_result?;
+    // printed after the restore so it lands on the real stdout for a pipeline to capture
+    if app.run_for.is_some() {
+        println!("{}", app.clocks[0].to_json());
+    }
 
     Ok(())
 }
 
+// puts the terminal back however main is left: normal exit, an error from `?` or a panic unwinding
+struct TerminalGuard {
+    title: bool, // the window title was taken over
+}
+
+impl Drop for TerminalGuard {
+    // mouse capture and the title aren't part of ratatui's own restore; the old title can't be read back, so it's cleared
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+        if self.title {
+            let _ = execute!(io::stdout(), SetTitle(""));
+        }
+        ratatui::restore();
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(version = env!("CARGO_PKG_VERSION"), about = "A terminal stopwatch and countdown timer")]
+struct Cli {
+    /// Count down from a duration such as 5:00 or 5m30s
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg, conflicts_with = "pomodoro")]
+    countdown: Option<Duration>,
+    /// Show the time of day instead of a stopwatch
+    #[arg(long, conflicts_with_all = ["countdown", "pomodoro"])]
+    clock: bool,
+    /// Start the clock running immediately, counting down straight away in countdown mode; other restored clocks stay paused
+    #[arg(long, alias = "start")]
+    autostart: bool,
+    /// Begin with this much time already on the clock, e.g. 1:02:03
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg)]
+    start_at: Option<Duration>,
+    /// Skip loading the previous session
+    #[arg(long)]
+    no_restore: bool,
+    /// Fraction shown after the seconds
+    #[arg(long, value_enum, default_value_t = Precision::Millis)]
+    precision: Precision,
+    /// Alternate work and break countdowns
+    #[arg(long)]
+    pomodoro: bool,
+    /// Repeat work and rest countdowns for a number of rounds
+    #[arg(long, conflicts_with_all = ["countdown", "pomodoro", "clock"])]
+    interval: bool,
+    /// Rounds in interval mode
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..))]
+    rounds: u32,
+    /// Length of a work phase [default: 25m for pomodoro, 20s for intervals]
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg)]
+    work: Option<Duration>,
+    /// Length of a break phase [default: 5m for pomodoro, 10s for intervals]
+    #[arg(long = "break", value_name = "DURATION", value_parser = duration_arg)]
+    rest: Option<Duration>,
+    /// Start with the bell turned off
+    #[arg(long)]
+    silent: bool,
+    /// Session name shown in the title
+    #[arg(long, value_name = "NAME")]
+    label: Option<String>,
+    /// Leave the terminal window title alone instead of showing the time in it
+    #[arg(long)]
+    no_title: bool,
+    /// Quit once the first clock reaches DURATION or its countdown ends, and print it with its laps as JSON; pairs with --autostart
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg)]
+    run_for: Option<Duration>,
+    /// Take start, pause, toggle, lap, split, reset and quit lines from this FIFO, made with mkfifo
+    #[arg(long, value_name = "PATH")]
+    control_fifo: Option<PathBuf>,
+    /// Never ask before quitting
+    #[arg(long)]
+    force_quit: bool,
+    /// Skip the interface: read start, pause, toggle, lap, split, reset or quit from stdin and print one JSON event per line
+    #[arg(long)]
+    emit_events: bool,
+    /// Redraw rate while the clock is running, 1 to 240
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=240))]
+    fps: u32,
+    /// Only the time, no border, laps or hints; a single-row terminal gets this anyway
+    #[arg(long)]
+    compact: bool,
+    /// Keep the colons solid while the clock runs
+    #[arg(long)]
+    no_blink: bool,
+    /// Font for the clock, falls back to a smaller one when the time doesn't fit
+    #[arg(long, value_enum, default_value = "plain")]
+    font: ClockFont,
+    /// Where the fraction of a second goes; below or hidden keeps the big digits still between seconds
+    #[arg(long, value_enum, default_value = "inline")]
+    fraction: FractionDisplay,
+    /// Draw the time in big block digits, same as --font blocks
+    #[arg(long, conflicts_with = "font")]
+    big: bool,
+    /// Show at most this many laps, newest first
+    #[arg(long, value_name = "N")]
+    max_laps: Option<usize>,
+    /// Record a lap automatically every DURATION, with the bell and a border flash, e.g. 30 or 1m
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg)]
+    lap_every: Option<Duration>,
+    /// Keep only the newest N laps in memory and in saves, numbering still counts the dropped ones
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    keep_laps: Option<u64>,
+    /// Pulse the clock each time it passes a multiple of DURATION
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg, default_value = "60")]
+    milestone: Duration,
+    /// No pulse at milestones
+    #[arg(long)]
+    no_milestones: bool,
+    /// Hide the wall-clock time next to each lap
+    #[arg(long)]
+    no_timestamps: bool,
+    /// Color theme, overrides the preset saved in ~/.clockwatch/theme.toml but not the colors set there
+    #[arg(long, value_enum)]
+    theme: Option<ThemeName>,
+    /// No colors, state is shown with bold, underline and reverse video; same as --theme mono
+    #[arg(long, conflicts_with = "theme")]
+    mono: bool,
+    /// How far + and - nudge a paused clock
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg, default_value = "1s")]
+    step: Duration,
+    /// Pause running clocks when the wall clock jumps further than this between frames, e.g. over a sleep
+    #[arg(long, value_name = "DURATION", value_parser = duration_arg, default_value = "2s")]
+    gap_threshold: Duration,
+    /// Keep counting through sleep and other wall-clock gaps
+    #[arg(long)]
+    no_gap_pause: bool,
+}
+
+impl Cli {
+    fn mode(&self) -> ClockMode {
+        if self.clock {
+            ClockMode::WallClock
+        } else if self.interval {
+            ClockMode::Interval { work: self.work.unwrap_or(Duration::from_secs(20)), rest: self.rest.unwrap_or(Duration::from_secs(10)), rounds: self.rounds }
+        } else if self.pomodoro {
+            ClockMode::Pomodoro { work: self.work.unwrap_or(Duration::from_secs(25 * 60)), rest: self.rest.unwrap_or(Duration::from_secs(5 * 60)) }
+        } else if let Some(target) = self.countdown {
+            ClockMode::Countdown { target }
+        } else {
+            ClockMode::Stopwatch
+        }
+    }
+
+    // NO_COLOR (https://no-color.org) only wins over the saved theme, not an explicit --theme;
+    // None leaves the choice to the saved theme
+    fn theme_name(&self, no_color: bool) -> Option<ThemeName> {
+        match self.theme {
+            Some(name) => Some(name),
+            None if self.mono || no_color => Some(ThemeName::Mono),
+            None => None,
+        }
+    }
+}
+
+fn duration_arg(text: &str) -> Result<Duration, String> {
+    parse_duration(text).ok_or_else(|| format!("invalid duration `{text}`, expected e.g. 90, 5:00, 1:02:03 or 1h2m3s with minutes and seconds under 60, up to {}h", MAX_DURATION.as_secs() / 3600))
+}
+
+// parses "SS", "MM:SS", "HH:MM:SS" or unit form like "1h2m3s";
+// only the leading field may go past 59, so 90:00 is fine but 99:99:99 isn't;
+// anything over MAX_DURATION is refused so a running clock can't overflow its elapsed time
+fn parse_duration(text: &str) -> Option<Duration> {
+    let duration = if text.ends_with(['h', 'm', 's']) { parse_unit_duration(text)? } else { parse_clock_duration(text)? };
+    (duration <= MAX_DURATION).then_some(duration)
+}
+
+fn parse_clock_duration(text: &str) -> Option<Duration> {
+    let parts = text.split(':').map(|part| part.parse::<u64>().ok()).collect::<Option<Vec<u64>>>()?;
+    let secs = match parts.as_slice() {
+        [s] => *s,
+        [m, s] if *s < 60 => m.checked_mul(60)?.checked_add(*s)?,
+        [h, m, s] if *m < 60 && *s < 60 => h.checked_mul(3600)?.checked_add(m * 60 + s)?,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
+// units must appear in h, m, s order and each at most once
+fn parse_unit_duration(text: &str) -> Option<Duration> {
+    let mut secs = 0;
+    let mut number = String::new();
+    let mut units = ['h', 'm', 's'].iter().peekable();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        while units.next_if(|unit| **unit != c).is_some() {}
+        let multiplier = match units.next()? {
+            'h' => 3600,
+            'm' => 60,
+            _ => 1,
+        };
+        // an overflowing amount is as invalid as a malformed one
+        secs = number.parse::<u64>().ok()?.checked_mul(multiplier)?.checked_add(secs)?;
+        number.clear();
+    }
+    number.is_empty().then_some(Duration::from_secs(secs))
+}
+
+const MAX_CLOCKS: usize = 9;
+const MAX_DURATION: Duration = Duration::from_secs(10_000 * 3600); // longest duration any flag accepts
+const STATUS_TIMEOUT: Duration = Duration::from_secs(3); // how long status messages stay up
+const COPY_FLASH_TIMEOUT: Duration = Duration::from_secs(1);
+const FASTEST_FLASH_TIMEOUT: Duration = Duration::from_millis(1500);
+const ALERT_DURATION: Duration = Duration::from_secs(2); // border flash after a countdown finishes
+const ALERT_BLINK: Duration = Duration::from_millis(250);
+const TOGGLE_DEBOUNCE: Duration = Duration::from_millis(80); // start/pause keys closer together than this are repeats
+const CONTROL_POLL: Duration = Duration::from_millis(100);
+const MILESTONE_PULSE: Duration = Duration::from_millis(600); // clock highlight after passing a milestone
+const AUTO_LAP_FLASH: Duration = Duration::from_millis(200); // one alert-colored blink of the border
+
 #[derive(Debug)]
 struct App {
-    clock: Clockwatch, // clockwatch widget
+    clocks: Vec<Clockwatch>, // independent stopwatches, never empty
+    active: usize, // index of the clock receiving commands
+    keybinds: Keybinds,
+    theme_config: ThemeConfig, // preset and color overrides, saved when cycled so the next run picks it up
+    theme: Theme,
+    show_help: bool, // help popup is open
+    help_scroll: usize, // help lines hidden above the popup on short terminals
+    confirm_quit: bool, // quit prompt is open
+    force_quit: bool, // quit without asking even with an active session
+    label: String, // session name shown in the title
+    input: Option<TextInput>, // open text prompt, if any
+    status: Option<(String, Instant)>, // transient message and when it expires
+    lap_scroll: LapScroll,
+    selected_lap: Option<usize>, // index into the active clock's laps under the j/k cursor
+    pending_g: bool, // first g of a gg seen, the next key decides
+    last_toggle: Option<Instant>, // last start/pause key, for dropping repeats
+    frame_budget: Duration, // minimum time between draws
+    step: Duration, // nudge applied by + and -
+    show_timestamps: bool, // wall-clock column in the lap list
+    font: ClockFont, // clock font, plain is a single line
+    fraction: FractionDisplay, // where the fraction of a second is drawn
+    compact: bool, // only the time, for tmux panes
+    frozen_display: Option<Duration>, // time held on screen while the active clock keeps going
+    blink: bool, // colons blink once a second while running
+    lap_distance: Option<f64>, // meters covered per lap, for the pace column
+    max_visible_laps: Option<usize>, // cap on listed laps, None lists as many as fit
+    lap_every: Option<Duration>, // --lap-every interval for automatic laps
+    milestone_every: Option<Duration>, // --milestone interval, None with --no-milestones
+    milestone_pulse: Duration, // time left on the clock's milestone highlight
+    start_button: Rect, // where the buttons were last drawn, for mouse hit-testing
+    lap_button: Rect,
+    screen: Rect, // area of the last draw, snapshots render at the same size
+    sound_enabled: bool, // ring the terminal bell on laps and finished countdowns
+    alert_remaining: Duration, // time left on the finished-countdown border flash
+    gap_threshold: Option<Duration>, // wall-clock jump between frames that pauses running clocks, None keeps counting
+    window_title: Option<String>, // last text put in the terminal title, None with --no-title
+    run_for: Option<Duration>, // --run-for limit on the first clock, reaching it quits
+    control: Option<Receiver<String>>, // lines from --control-fifo
+    persist: bool, // write the session on exit, off when the run started in another mode
     exit: bool, // bool for exit
-    last_frame: Instant
+    last_frame: Instant,
+    last_wall: SystemTime, // wall-clock time of the last frame, it keeps going while the machine sleeps
 }
 
 impl App {
+    // everything configurable comes from the command line, the rest starts idle
+    fn new(args: &Cli, clocks: Vec<Clockwatch>, theme_config: ThemeConfig, keybinds: Keybinds, control: Option<Receiver<String>>) -> Self {
+        App {
+            clocks,
+            active: 0,
+            keybinds,
+            theme_config,
+            theme: theme_config.theme(),
+            show_help: false,
+            help_scroll: 0,
+            confirm_quit: false,
+            force_quit: args.force_quit,
+            label: args.label.clone().unwrap_or_default(),
+            input: None,
+            status: None,
+            lap_scroll: LapScroll::default(),
+            selected_lap: None,
+            pending_g: false,
+            last_toggle: None,
+            frame_budget: Duration::from_secs(1) / args.fps,
+            step: args.step,
+            show_timestamps: !args.no_timestamps,
+            font: if args.big { ClockFont::Blocks } else { args.font },
+            fraction: args.fraction,
+            compact: args.compact,
+            frozen_display: None,
+            blink: !args.no_blink,
+            lap_distance: None,
+            max_visible_laps: args.max_laps,
+            lap_every: args.lap_every.filter(|every| !every.is_zero()),
+            milestone_every: (!args.no_milestones).then_some(args.milestone),
+            milestone_pulse: Duration::ZERO,
+            start_button: Rect::default(),
+            lap_button: Rect::default(),
+            screen: Rect::default(),
+            sound_enabled: !args.silent,
+            alert_remaining: Duration::ZERO,
+            gap_threshold: (!args.no_gap_pause).then_some(args.gap_threshold),
+            window_title: (!args.no_title).then(String::new),
+            run_for: args.run_for,
+            control,
+            persist: args.mode() == ClockMode::Stopwatch,
+            exit: false,
+            last_frame: Instant::now(),
+            last_wall: SystemTime::now(),
+        }
+    }
+
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        terminal.draw(|frame| self.draw(frame))?;
+        let mut last_draw = Instant::now();
+        let mut drawn_second = Local::now().timestamp();
+
         while !self.exit {
+            // block until the next frame is due while running, or mostly sleep while paused
+            let timeout = if self.animating() {
+                self.frame_budget.saturating_sub(last_draw.elapsed())
+            } else {
+                // wake up for the wall clock's next second, or sooner if a status message expires
+                let next_second = Duration::from_secs(1).saturating_sub(Duration::from_nanos(Local::now().timestamp_subsec_nanos().into()));
+                self.status.as_ref().map_or(next_second, |(_, expires_at)| {
+                    next_second.min(expires_at.saturating_duration_since(Instant::now()))
+                })
+            };
+            // a control FIFO is checked at least this often
+            let timeout = if self.control.is_some() { timeout.min(CONTROL_POLL) } else { timeout };
+            let had_event = self.handle_events(timeout)?;
+
+            let was_animating = self.animating();
+            let had_status = self.status.is_some();
             let dt = self.last_frame.elapsed();
             self.last_frame = Instant::now();
-
-            self.handle_events()?;
+            // a wall clock set backwards isn't a gap
+            let wall_dt = self.last_wall.elapsed().unwrap_or_default();
+            self.last_wall = SystemTime::now();
+            self.pause_after_gap(wall_dt, dt);
             self.update(dt);
+            self.update_window_title();
 
-            terminal.draw(|frame| self.draw(frame))?;
+            // the display only changes while running or flashing, so skip redundant draws otherwise
+            let status_expired = self.status.is_none() && had_status;
+            let second_ticked = Local::now().timestamp() != drawn_second;
+            if had_event || status_expired || second_ticked || (was_animating && last_draw.elapsed() >= self.frame_budget) {
+                // a burst of input shouldn't push the frame rate past the budget
+                thread::sleep(self.frame_budget.saturating_sub(last_draw.elapsed()));
+                terminal.draw(|frame| self.draw(frame))?;
+                last_draw = Instant::now();
+                drawn_second = Local::now().timestamp();
+            }
         }
         Ok(())
     }
 
+    // the session file only restores stopwatches, so a run started as a countdown, pomodoro, interval or
+    // wall clock leaves the saved one alone, and clocks switched to another mode aren't written as stopwatches
+    pub fn save_session(&self) -> io::Result<()> {
+        let sessions = self.sessions();
+        if !self.persist || sessions.is_empty() {
+            return Ok(());
+        }
+        let path = session_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(&sessions)?)
+    }
+
+    fn sessions(&self) -> Vec<Session> {
+        self.clocks.iter().filter(|clock| clock.mode() == ClockMode::Stopwatch).map(Session::from_clock).collect()
+    }
+
+    fn set_status(&mut self, message: String, timeout: Duration) {
+        self.status = Some((message, Instant::now() + timeout));
+    }
+
+    // a written export or save marks the laps as kept, a failed one shows `<action> failed: <error>`
+    fn report_write(&mut self, written: io::Result<()>, action: &str, done: String) {
+        let message = match written {
+            Ok(()) => {
+                self.clock_mut().mark_saved();
+                done
+            }
+            Err(err) => format!("{action} failed: {err}"),
+        };
+        self.set_status(message, STATUS_TIMEOUT);
+    }
+
+    // copies the active clock's reading, clipboard failures are reported rather than fatal
+    fn copy_time(&mut self) {
+        let clock = self.clock();
+        let text = Clockwatch::duration_into_text(clock.display_time(), clock.format, clock.precision);
+        let message = copy_text(Clipboard::new(), text);
+        self.set_status(message, COPY_FLASH_TIMEOUT);
+    }
+
+    // the choice is saved right away, a failed save only costs persistence
+    fn cycle_theme(&mut self) {
+        self.theme_config.name = self.theme_config.name.next();
+        self.theme = self.theme_config.theme();
+        let message = match self.theme_config.save() {
+            Ok(()) => format!("Theme: {}", self.theme_config.name.label()),
+            Err(err) => format!("Theme: {} (not saved: {err})", self.theme_config.name.label()),
+        };
+        self.set_status(message, STATUS_TIMEOUT);
+    }
+
+    fn ring_bell(&self) {
+        if self.sound_enabled {
+            // the bell doesn't move the cursor so it can't disturb the drawn frame
+            let _ = execute!(io::stdout(), Print("\x07"));
+        }
+    }
+
+    fn lap(&mut self) {
+        if self.clock().mode() == ClockMode::WallClock {
+            return;
+        }
+        if self.clock_mut().lap() {
+            self.set_status("Fastest!".to_string(), FASTEST_FLASH_TIMEOUT);
+        }
+        self.ring_bell();
+    }
+
+    fn split(&mut self) {
+        if self.clock().mode() == ClockMode::WallClock {
+            return;
+        }
+        self.clock_mut().split();
+        self.ring_bell();
+    }
+
+    fn clock(&self) -> &Clockwatch {
+        &self.clocks[self.active]
+    }
+
+    fn clock_mut(&mut self) -> &mut Clockwatch {
+        &mut self.clocks[self.active]
+    }
+
+    // whether the screen needs redrawing every frame
+    fn animating(&self) -> bool {
+        self.clocks.iter().any(|clock| clock.running() || clock.alarm_active()) || !self.alert_remaining.is_zero() || !self.milestone_pulse.is_zero()
+    }
+
+    fn switch_clock(&mut self, forward: bool) {
+        let count = self.clocks.len();
+        self.active = if forward { (self.active + 1) % count } else { (self.active + count - 1) % count };
+        self.lap_scroll.offset = 0;
+        self.selected_lap = None;
+        self.frozen_display = None;
+    }
+
+    // new clocks share the display settings of the active one
+    fn add_clock(&mut self) {
+        if self.clocks.len() >= MAX_CLOCKS {
+            return;
+        }
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.format = self.clock().format;
+        clock.precision = self.clock().precision;
+        clock.set_keep_laps(self.clock().keep_laps());
+        self.clocks.push(clock);
+        self.active = self.clocks.len() - 1;
+        self.lap_scroll.offset = 0;
+        self.selected_lap = None;
+        self.frozen_display = None;
+    }
+
+    // the last remaining clock can't be removed
+    fn remove_clock(&mut self) {
+        if self.clocks.len() <= 1 {
+            return;
+        }
+        self.clocks.remove(self.active);
+        self.active = self.active.min(self.clocks.len() - 1);
+        self.lap_scroll.offset = 0;
+        self.selected_lap = None;
+        self.frozen_display = None;
+    }
+
+    // the monotonic clock may or may not count a sleep depending on the platform, so a wall-clock
+    // jump pauses running clocks as of the previous frame instead of letting them leap ahead
+    fn pause_after_gap(&mut self, wall_dt: Duration, dt: Duration) {
+        if self.gap_threshold.is_none_or(|threshold| wall_dt <= threshold) {
+            return;
+        }
+        let mut paused = false;
+        for clock in self.clocks.iter_mut().filter(|clock| clock.running()) {
+            clock.pause();
+            let before_gap = clock.elapsed().saturating_sub(dt);
+            clock.set_elapsed(before_gap);
+            paused = true;
+        }
+        if paused {
+            self.set_status(format!("Paused after a {}s gap", wall_dt.as_secs()), STATUS_TIMEOUT);
+        }
+    }
+
+    // whole seconds only, so the title changes at most once a second
+    fn window_title_text(&self) -> String {
+        let clock = self.clock();
+        let time = if clock.mode() == ClockMode::WallClock {
+            Local::now().format("%H:%M:%S").to_string()
+        } else if clock.finished() {
+            "DONE".to_string()
+        } else {
+            Clockwatch::duration_into_text(clock.display_time(), clock.format, Precision::Seconds)
+        };
+        let state = if clock.running() { "▶" } else { "⏸" };
+        let name = if self.label.is_empty() { "Clockwatch" } else { &self.label };
+        format!("{state} {time} - {name}")
+    }
+
+    // only written when the text changes, terminals can be slow to retitle
+    fn update_window_title(&mut self) {
+        let Some(shown) = &self.window_title else {
+            return;
+        };
+        let title = self.window_title_text();
+        if *shown != title {
+            let _ = execute!(io::stdout(), SetTitle(&title));
+            self.window_title = Some(title);
+        }
+    }
+
+    // holds the active clock's reading on screen, timing carries on underneath
+    fn toggle_freeze(&mut self) {
+        self.frozen_display = match self.frozen_display {
+            Some(_) => None,
+            None if self.clock().mode() == ClockMode::WallClock => None,
+            None => Some(self.clock().display_time()),
+        };
+    }
+
+    // a single row has no room for anything but the time, with or without --compact
+    fn is_compact(&self, area: Rect) -> bool {
+        self.compact || area.height == 1
+    }
+
+    // `dt` only drives animations, clock time comes from each clock's start instant
     pub fn update(&mut self, dt: Duration) {
-        self.clock.update(dt);
+        self.alert_remaining = self.alert_remaining.saturating_sub(dt);
+        self.milestone_pulse = self.milestone_pulse.saturating_sub(dt);
+
+        // background clocks keep counting too, only the active one pulses
+        let mut finished = false;
+        let mut auto_laps = 0;
+        for (i, clock) in self.clocks.iter_mut().enumerate() {
+            finished |= clock.update();
+            if let Some(every) = self.lap_every {
+                auto_laps += clock.record_auto_laps(every);
+            }
+            if let Some(every) = self.milestone_every
+                && clock.passed_milestone(every)
+                && i == self.active {
+                self.milestone_pulse = MILESTONE_PULSE;
+            }
+        }
+        if finished {
+            self.alert_remaining = ALERT_DURATION;
+            self.ring_bell();
+        } else if auto_laps > 0 {
+            self.alert_remaining = self.alert_remaining.max(AUTO_LAP_FLASH);
+            self.ring_bell();
+        }
+        if let Some((_, expires_at)) = &self.status
+            && Instant::now() >= *expires_at {
+            self.status = None;
+        }
+        // watches the clock the run started with whichever tab is open, and a countdown that ends
+        // first ends the run too; paused so the printed time is the one it stopped at
+        if let Some(limit) = self.run_for
+            && (self.clocks[0].elapsed() >= limit || self.clocks[0].finished()) {
+            self.clocks[0].pause();
+            self.exit = true;
+        }
     }
 
-    pub fn draw(&self, frame: &mut Frame) {
-        frame.render_widget(&self.clock, frame.area());
-        frame.render_widget(self, frame.area());
+    pub fn draw(&mut self, frame: &mut Frame) {
+        self.render_into(frame.area(), frame.buffer_mut());
     }
 
-    pub fn handle_events(&mut self) -> io::Result<()> {
-        while event::poll(Duration::from_millis(0))? {
-            if let event::Event::Key(key_event) = event::read()? {
-                if key_event.kind == KeyEventKind::Press {
+    // the whole ui into any buffer, the terminal's own or a detached one for a snapshot
+    fn render_into(&mut self, area: Rect, buf: &mut Buffer) {
+        self.screen = area;
+        if self.is_compact(area) {
+            (self.start_button, self.lap_button) = (Rect::default(), Rect::default());
+            StatefulWidget::render(ClockView { clock: &self.clocks[self.active], theme: &self.theme, timestamps: false, font: self.font, fraction: self.fraction, compact: true, frozen: self.frozen_display, blink: self.blink, lap_distance: None, max_laps: None, selected: None, pulse: !self.milestone_pulse.is_zero() }, area, buf, &mut self.lap_scroll);
+            // prompts and popups still draw so every key keeps working
+            Widget::render(&*self, area, buf);
+            return;
+        }
+        let block = self.frame_block(area.width);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // the clock stays above the button and status rows so nothing draws over laps
+        // the wall clock has nothing to start or lap, so its buttons are hidden
+        (self.start_button, self.lap_button) = if self.clock().mode() == ClockMode::WallClock {
+            (Rect::default(), Rect::default())
+        } else {
+            button_areas(inner)
+        };
+        let mut clock_area = inner;
+        clock_area.height = clock_area.height.saturating_sub(2);
+        if self.clocks.len() > 1 {
+            // whole seconds keep the strip narrow enough for a full set of clocks
+            let tabs = Tabs::new(self.clocks.iter().enumerate().map(|(i, clock)| {
+                format!("{} {}", i + 1, Clockwatch::duration_into_text(clock.display_time(), TimeFormat::Hms, Precision::Seconds))
+            }))
+                .select(self.active)
+                .highlight_style(self.theme.key);
+            tabs.render(Rect { height: 1.min(clock_area.height), ..clock_area }, buf);
+            clock_area.y += 1;
+            clock_area.height = clock_area.height.saturating_sub(1);
+        }
+        StatefulWidget::render(ClockView { clock: &self.clocks[self.active], theme: &self.theme, timestamps: self.show_timestamps, font: self.font, fraction: self.fraction, compact: false, frozen: self.frozen_display, blink: self.blink, lap_distance: self.lap_distance, max_laps: self.max_visible_laps, selected: self.selected_lap, pulse: !self.milestone_pulse.is_zero() }, clock_area, buf, &mut self.lap_scroll);
+        // clamp here so holding Down past the end doesn't need as many Ups to come back
+        let visible = area.height.saturating_sub(2) as usize;
+        self.help_scroll = self.help_scroll.min(help_lines(&self.keybinds, &self.theme).len().saturating_sub(visible));
+        Widget::render(&*self, area, buf);
+    }
+
+    // the screen as plain text, one line per row with trailing blanks trimmed
+    fn snapshot(&mut self) -> String {
+        let area = self.screen;
+        let mut buf = Buffer::empty(area);
+        self.render_into(area, &mut buf);
+        buffer_text(&buf)
+    }
+
+    // waits up to `timeout` for the first event, then drains whatever else is queued
+    pub fn handle_events(&mut self, timeout: Duration) -> io::Result<bool> {
+        let mut had_event = false;
+        let mut wait = timeout;
+        while event::poll(wait)? {
+            had_event = true;
+            wait = Duration::ZERO;
+            match event::read()? {
+                event::Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                     self.handle_key_pressed_event(key_event)?;
                 }
+                event::Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event),
+                // nothing to update, returning true redraws straight away at the new size
+                event::Event::Resize(_, _) => {}
+                _ => {}
             }
         }
-        Ok(())
+        let had_control = self.drain_control();
+        Ok(had_event || had_control)
     }
 
-    pub fn handle_key_pressed_event(&mut self, key_event: KeyEvent) -> io::Result<()>{
-        match key_event.code {
-            KeyCode::Char('q') => {
+    // whatever came in over the FIFO since the last frame, never waiting on it
+    fn drain_control(&mut self) -> bool {
+        let lines = self.control.as_ref().map_or(vec![], |control| control.try_iter().collect::<Vec<_>>());
+        for line in &lines {
+            self.handle_control_line(line);
+        }
+        !lines.is_empty()
+    }
+
+    // start and pause only go one way, unlike the key that toggles; lines aren't keystrokes, so they
+    // reach the clock past an open help screen, quit prompt or text input and leave those open,
+    // and quit exits straight away since a script has no way to answer the prompt
+    fn handle_control_line(&mut self, line: &str) {
+        let command = match line.trim() {
+            "" => return,
+            "start" if self.clock().running() => return,
+            "pause" if !self.clock().running() => return,
+            "start" | "pause" | "toggle" => Command::StartPause,
+            "lap" => Command::Lap,
+            "split" => Command::Split,
+            "reset" => Command::Reset,
+            "quit" => {
                 self.exit = true;
-                Ok(())
+                return;
             }
-            KeyCode::Char(' ') => {
-                self.clock.toggle_start_pause();
-                Ok(())
+            other => {
+                self.set_status(format!("Unknown control command: {other}"), STATUS_TIMEOUT);
+                return;
             }
-            KeyCode::Char('l') => {
-                self.clock.lap();
-                Ok(())
+        };
+        self.apply(command);
+    }
+
+    // clicks are hit-tested against the button areas from the last draw; like keys, they can't
+    // get past help, the quit prompt, a text input or a ringing alarm
+    pub fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let modal = self.show_help || self.confirm_quit || self.input.is_some() || self.clocks.iter().any(|clock| clock.alarm_active());
+        if modal || mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return;
+        }
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        if self.start_button.contains(position) {
+            self.apply(Command::StartPause);
+        } else if self.lap_button.contains(position) {
+            self.apply(Command::Lap);
+        }
+    }
+
+    fn handle_input_key(&mut self, code: KeyCode) {
+        let Some(input) = &mut self.input else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => input.buffer.push(c),
+            KeyCode::Backspace => {
+                input.buffer.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                if let Some(input) = self.input.take() {
+                    match input.purpose {
+                        InputPurpose::Label => self.label = input.buffer,
+                        InputPurpose::LapLabel => self.clock_mut().label_last_lap(input.buffer),
+                        InputPurpose::LapDistance => {
+                            // empty clears the distance, anything that isn't a positive number is rejected
+                            if input.buffer.trim().is_empty() {
+                                self.lap_distance = None;
+                            } else {
+                                match input.buffer.trim().parse::<f64>() {
+                                    Ok(meters) if meters > 0.0 && meters.is_finite() => self.lap_distance = Some(meters),
+                                    _ => self.set_status(format!("Not a distance: {}", input.buffer), STATUS_TIMEOUT),
+                                }
+                            }
+                        }
+                        InputPurpose::Command => {
+                            if let Err(message) = self.parse_command(&input.buffer) {
+                                self.set_status(message, STATUS_TIMEOUT);
+                            }
+                        }
+                        InputPurpose::Save => {
+                            let written = self.clock().save_to(Path::new(&input.buffer));
+                            self.report_write(written, "Save", format!("Saved to {}", input.buffer));
+                        }
+                        InputPurpose::Load => {
+                            let message = match Clockwatch::load_from(Path::new(&input.buffer)) {
+                                Ok(mut clock) => {
+                                    // the cap isn't saved with the clock, the loaded one keeps this run's
+                                    clock.set_keep_laps(self.clock().keep_laps());
+                                    // a different clock now, so nothing carries over from the old one, like a tab switch
+                                    *self.clock_mut() = clock;
+                                    self.lap_scroll.offset = 0;
+                                    self.selected_lap = None;
+                                    self.frozen_display = None;
+                                    format!("Loaded {}", input.buffer)
+                                }
+                                Err(err) => format!("Load failed: {err}"),
+                            };
+                            self.set_status(message, STATUS_TIMEOUT);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle_key_pressed_event(&mut self, key_event: KeyEvent) -> io::Result<()>{
+        // the first key after a countdown alarm only acknowledges it
+        if self.clocks.iter().any(|clock| clock.alarm_active()) {
+            for clock in &mut self.clocks {
+                clock.acknowledge_alarm();
+            }
+            return Ok(());
+        }
+
+        // the quit prompt only answers to y, n and Esc, anything else is ignored
+        if self.confirm_quit {
+            match key_event.code {
+                KeyCode::Char('y') => self.exit = true,
+                KeyCode::Char('n') | KeyCode::Esc => self.confirm_quit = false,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // text entry captures every key so typing can't trigger clock controls
+        if self.input.is_some() {
+            self.handle_input_key(key_event.code);
+            return Ok(());
+        }
+
+        // help scrolls with the arrows, any other key closes it
+        if self.show_help {
+            match key_event.code {
+                KeyCode::Up | KeyCode::Char('k') => self.help_scroll = self.help_scroll.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => self.help_scroll += 1,
+                _ => self.show_help = false,
+            }
+            return Ok(());
+        }
+
+        // `gg` jumps to the newest lap, a lone g waits for the next key; a keybind on g wins over both
+        if key_event.code == KeyCode::Char('g') && !self.keybinds.binds('g') {
+            if std::mem::take(&mut self.pending_g) {
+                self.apply(Command::ScrollTop);
+            } else {
+                self.pending_g = true;
+            }
+            return Ok(());
+        }
+        self.pending_g = false;
+
+        if let Some(command) = key_to_command(&self.keybinds, key_event) {
+            // with a lap under the cursor d deletes that lap instead of the clock
+            let command = if command == Command::RemoveClock && self.selected_lap.is_some() { Command::DeleteLap } else { command };
+            // some terminals send a held or bouncing space twice, which would pause straight after starting;
+            // laps and resets are never dropped
+            if command == Command::StartPause {
+                let now = Instant::now();
+                if self.last_toggle.is_some_and(|last| now.duration_since(last) < TOGGLE_DEBOUNCE) {
+                    return Ok(());
+                }
+                self.last_toggle = Some(now);
+            }
+            self.apply(command);
+        }
+        Ok(())
+    }
+
+    // runs a `:` command line against the active clock, the error is shown as the status
+    pub fn parse_command(&mut self, line: &str) -> Result<(), String> {
+        let (name, arg) = line.trim().split_once(' ').map_or((line.trim(), ""), |(name, arg)| (name, arg.trim()));
+        let duration = |arg: &str| parse_duration(arg).ok_or_else(|| format!("Not a duration: {arg}"));
+        match (name, arg) {
+            ("", _) => {}
+            ("set", arg) if !arg.is_empty() => {
+                let elapsed = duration(arg)?;
+                self.clock_mut().set_elapsed(elapsed);
+            }
+            ("countdown", arg) if !arg.is_empty() => {
+                let target = duration(arg)?;
+                self.clock_mut().set_countdown(target);
+            }
+            ("clearlaps", "") => {
+                self.clock_mut().clear_laps();
+                self.lap_scroll.offset = 0;
+            }
+            ("export", arg) if !arg.is_empty() => {
+                // the extension picks the format, anything but .json gets CSV
+                let path = Path::new(arg);
+                let written = if path.extension().is_some_and(|ext| ext == "json") {
+                    fs::write(path, self.clock().to_json())
+                } else {
+                    self.clock().export_csv(path)
+                };
+                written.map_err(|err| format!("Export failed: {err}"))?;
+                self.clock_mut().mark_saved();
+                self.set_status(format!("Written to {arg}"), STATUS_TIMEOUT);
+            }
+            ("set" | "countdown", _) => return Err(format!("Usage: :{name} <time>")),
+            ("export", _) => return Err("Usage: :export <path>".to_string()),
+            ("clearlaps", _) => return Err("Usage: :clearlaps".to_string()),
+            (name, _) => return Err(format!("Unknown command: {name}")),
+        }
+        Ok(())
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Quit => {
+                // nothing to lose once every lap is exported or saved, so it quits straight away
+                let in_use = self.clocks.iter().any(|clock| clock.running() || clock.unsaved());
+                if in_use && !self.force_quit {
+                    self.confirm_quit = true;
+                } else {
+                    self.exit = true;
+                }
+            }
+            Command::StartPause => self.clock_mut().toggle(),
+            Command::Lap => self.lap(),
+            Command::Split => self.split(),
+            Command::UndoLap => {
+                self.clock_mut().undo_lap();
+            }
+            Command::Reset => self.clock_mut().reset(),
+            Command::Nudge { forward } => {
+                let step = self.step;
+                self.clock_mut().adjust(step, forward);
+            }
+            Command::RewindToLap => {
+                if let Some(at) = self.clock_mut().rewind_to_last_lap() {
+                    let clock = self.clock();
+                    let message = format!("Rewound to {}", Clockwatch::duration_into_text(at, clock.format, clock.precision));
+                    self.set_status(message, STATUS_TIMEOUT);
+                }
+            }
+            Command::ToggleFreeze => self.toggle_freeze(),
+            Command::ToggleMode => self.clock_mut().toggle_mode(),
+            Command::CycleFormat => self.clock_mut().cycle_format(),
+            Command::CyclePrecision => self.clock_mut().cycle_precision(),
+            Command::ExportCsv => {
+                let written = self.clock().export_csv(Path::new("laps.csv"));
+                self.report_write(written, "Export", "Laps written to laps.csv".to_string());
+            }
+            Command::ExportJson => {
+                let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                let path = PathBuf::from(format!("session-{secs}.json"));
+                let written = fs::write(&path, self.clock().to_json());
+                self.report_write(written, "Export", format!("Session written to {}", path.display()));
+            }
+            // not an export of the laps, so they stay unsaved
+            Command::Snapshot => {
+                let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                let path = PathBuf::from(format!("screen-{secs}.txt"));
+                let message = match fs::write(&path, self.snapshot()) {
+                    Ok(()) => format!("Screen written to {}", path.display()),
+                    Err(err) => format!("Snapshot failed: {err}"),
+                };
+                self.set_status(message, STATUS_TIMEOUT);
+            }
+            Command::ExportLapsCsv => {
+                let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                let path = PathBuf::from(format!("laps-{secs}.csv"));
+                let written = self.clock().export_laps_csv(&path);
+                self.report_write(written, "Export", format!("Laps written to {}", path.display()));
+            }
+            // the first move puts the cursor on the top visible row
+            Command::ScrollUp => self.select_row(self.selected_row().map_or(self.lap_scroll.offset, |row| row.saturating_sub(1))),
+            Command::ScrollDown => self.select_row(self.selected_row().map_or(self.lap_scroll.offset, |row| row + 1)),
+            // clamped against the visible window on the next draw, a cursor moves along with the page
+            Command::ScrollHalfPage { down } => {
+                let half = (self.lap_scroll.visible / 2).max(1);
+                self.lap_scroll.offset = if down { self.lap_scroll.offset + half } else { self.lap_scroll.offset.saturating_sub(half) };
+                if let Some(row) = self.selected_row() {
+                    self.select_row(if down { row + half } else { row.saturating_sub(half) });
+                }
+            }
+            Command::ScrollTop => {
+                self.lap_scroll.offset = 0;
+                self.select_row(0);
+            }
+            Command::ScrollBottom => {
+                self.lap_scroll.offset = usize::MAX;
+                self.select_row(usize::MAX);
+            }
+            Command::Deselect => self.selected_lap = None,
+            Command::DeleteLap => self.delete_selected_lap(),
+            Command::NextClock => self.switch_clock(true),
+            Command::PrevClock => self.switch_clock(false),
+            Command::AddClock => self.add_clock(),
+            Command::RemoveClock => self.remove_clock(),
+            Command::CopyTime => self.copy_time(),
+            Command::LabelLap => {
+                let current = self.clock().laps().back().map(|lap| lap.label.clone().unwrap_or_default());
+                if let Some(buffer) = current {
+                    self.input = Some(TextInput { purpose: InputPurpose::LapLabel, buffer });
+                }
+            }
+            Command::Save => self.input = Some(TextInput { purpose: InputPurpose::Save, buffer: "clock.json".to_string() }),
+            Command::Load => self.input = Some(TextInput { purpose: InputPurpose::Load, buffer: "clock.json".to_string() }),
+            Command::Rename => self.input = Some(TextInput { purpose: InputPurpose::Label, buffer: self.label.clone() }),
+            Command::ToggleSound => {
+                self.sound_enabled = !self.sound_enabled;
+                let message = if self.sound_enabled { "Sound on" } else { "Sound off" };
+                self.set_status(message.to_string(), STATUS_TIMEOUT);
+            }
+            Command::SetLapDistance => {
+                let buffer = self.lap_distance.map(|meters| meters.to_string()).unwrap_or_default();
+                self.input = Some(TextInput { purpose: InputPurpose::LapDistance, buffer });
+            }
+            Command::CycleFont => {
+                self.font = self.font.next();
+                self.set_status(format!("Font: {}", self.font.label()), STATUS_TIMEOUT);
+            }
+            Command::CycleFraction => {
+                self.fraction = self.fraction.next();
+                self.set_status(format!("Fraction: {}", self.fraction.label()), STATUS_TIMEOUT);
+            }
+            Command::CycleTheme => self.cycle_theme(),
+            Command::OpenCommandLine => self.input = Some(TextInput { purpose: InputPurpose::Command, buffer: String::new() }),
+            Command::ShowHelp => {
+                self.show_help = true;
+                self.help_scroll = 0;
             }
-            _ => {Ok(())}
         }
+        // undo, reset and the like can take the selected lap away
+        let laps = self.clock().laps().len();
+        self.selected_lap = self.selected_lap.filter(|&index| index < laps);
+    }
+
+    // the list shows the newest lap first, so row 0 is the last index
+    fn selected_row(&self) -> Option<usize> {
+        self.selected_lap.map(|index| self.clock().laps().len().saturating_sub(index + 1))
+    }
+
+    // past the oldest lap the cursor stays on it, with no laps there's nothing to select
+    fn select_row(&mut self, row: usize) {
+        let laps = self.clock().laps().len();
+        self.selected_lap = (laps > 0).then(|| laps - 1 - row.min(laps - 1));
+    }
+
+    // the cursor stays on the same row, which now holds the next older lap
+    fn delete_selected_lap(&mut self) {
+        let Some(index) = self.selected_lap else {
+            return;
+        };
+        self.clock_mut().delete_lap(index);
+        self.selected_lap = (!self.clock().laps().is_empty()).then(|| index.saturating_sub(1));
     }
 }
 
-impl Widget for &App {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+// everything a key can ask the app to do, so the state changes can run without a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Quit,
+    StartPause,
+    Lap,
+    Split,
+    UndoLap,
+    Reset,
+    Nudge { forward: bool }, // step a paused clock by the --step amount
+    RewindToLap,
+    ToggleFreeze,
+    ToggleMode,
+    CycleFormat,
+    CyclePrecision,
+    ExportCsv,
+    ExportLapsCsv,
+    ExportJson,
+    Snapshot, // the current screen as text
+    ScrollUp,
+    ScrollDown,
+    ScrollHalfPage { down: bool },
+    ScrollTop, // newest lap
+    ScrollBottom, // oldest lap
+    Deselect, // drop the lap cursor
+    DeleteLap, // the lap under the cursor
+    NextClock,
+    PrevClock,
+    AddClock,
+    RemoveClock,
+    CopyTime,
+    LabelLap,
+    Save,
+    Load,
+    Rename,
+    ToggleSound,
+    SetLapDistance,
+    CycleFont,
+    CycleFraction,
+    CycleTheme,
+    OpenCommandLine,
+    ShowHelp,
+}
+
+// remappable keys are checked first so a custom binding can shadow a fixed one
+fn key_to_command(keybinds: &Keybinds, key_event: KeyEvent) -> Option<Command> {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key_event.modifiers.contains(KeyModifiers::ALT);
+    let command = match key_event.code {
+        KeyCode::Char('d') if ctrl => Command::ScrollHalfPage { down: true },
+        KeyCode::Char('u') if ctrl => Command::ScrollHalfPage { down: false },
+        // any other chord would otherwise act as its bare letter, so Ctrl+R would reset
+        _ if ctrl || alt => return None,
+        KeyCode::Char(c) if c == keybinds.quit => Command::Quit,
+        KeyCode::Char(c) if c == keybinds.start_pause => Command::StartPause,
+        KeyCode::Char(c) if c == keybinds.lap => Command::Lap,
+        KeyCode::Char(c) if c == keybinds.reset => Command::Reset,
+        KeyCode::Backspace | KeyCode::Char('u') => Command::UndoLap,
+        KeyCode::Char('+') | KeyCode::Char('=') => Command::Nudge { forward: true },
+        KeyCode::Char('-') => Command::Nudge { forward: false },
+        KeyCode::Char('c') => Command::RewindToLap,
+        KeyCode::Char('z') => Command::ToggleFreeze,
+        KeyCode::Char('m') => Command::ToggleMode,
+        KeyCode::Char('t') => Command::CycleFormat,
+        KeyCode::Char('p') => Command::CyclePrecision,
+        KeyCode::Char('w') => Command::ExportCsv,
+        KeyCode::Char('e') => Command::ExportJson,
+        KeyCode::Char('E') => Command::ExportLapsCsv,
+        KeyCode::F(2) => Command::Snapshot,
+        KeyCode::Up | KeyCode::Char('k') => Command::ScrollUp,
+        KeyCode::Down | KeyCode::Char('j') => Command::ScrollDown,
+        KeyCode::Char('G') => Command::ScrollBottom,
+        KeyCode::Esc => Command::Deselect,
+        KeyCode::Tab => Command::NextClock,
+        KeyCode::BackTab => Command::PrevClock,
+        KeyCode::Char('a') => Command::AddClock,
+        KeyCode::Char('d') => Command::RemoveClock,
+        KeyCode::Char('y') => Command::CopyTime,
+        KeyCode::Char('L') => Command::LabelLap,
+        KeyCode::Char('S') => Command::Save,
+        KeyCode::Char('O') => Command::Load,
+        KeyCode::Char('n') => Command::Rename,
+        KeyCode::Char('b') => Command::ToggleSound,
+        KeyCode::Char('s') => Command::Split,
+        KeyCode::Char('D') => Command::SetLapDistance,
+        KeyCode::Char('f') => Command::CycleFont,
+        KeyCode::Char('v') => Command::CycleFraction,
+        KeyCode::Char('T') => Command::CycleTheme,
+        KeyCode::Char(':') => Command::OpenCommandLine,
+        KeyCode::Char('?') => Command::ShowHelp,
+        _ => return None,
+    };
+    Some(command)
+}
+
+impl App {
+    fn title(&self) -> Line<'static> {
+        if self.label.is_empty() {
+            Line::from(" Clockwatch rust app ".set_style(self.theme.title)).centered()
+        } else {
+            Line::from(format!(" {} ", self.label).set_style(self.theme.title)).centered()
+        }
+    }
 
-        let title = Line::from(" Clockwatch rust app ".bold()).centered();
-        
-        let instructions = Line::from(vec![
-            " Pause/Start ".into(),
-            "<Space>".blue().bold(),
-            " Lap ".into(),
-            "<l>".blue().bold(),
+    // the outer border with the title and key hints, everything else draws inside or on top of it
+    fn frame_block(&self, width: u16) -> Block<'static> {
+        // the wall clock can't be started or lapped, so those hints are left out
+        let mut hints = Vec::new();
+        if self.clock().mode() != ClockMode::WallClock {
+            hints.extend([
+                " Pause/Start ".into(),
+                format!("<{}>", key_label(self.keybinds.start_pause)).set_style(self.theme.key),
+                " Lap ".into(),
+                format!("<{}>", key_label(self.keybinds.lap)).set_style(self.theme.key),
+                " Undo ".into(),
+                "<u>".set_style(self.theme.key),
+            ]);
+        }
+        hints.extend([
+            " Reset ".into(),
+            format!("<{}>", key_label(self.keybinds.reset)).set_style(self.theme.key),
+            " Mode ".into(),
+            "<m>".set_style(self.theme.key),
+            " Format ".into(),
+            "<t>".set_style(self.theme.key),
             " Exit ".into(),
-            "<q>".blue().bold(),
-        ]).centered();
+            format!("<{}>", key_label(self.keybinds.quit)).set_style(self.theme.key),
+            " Help ".into(),
+            "<?>".set_style(self.theme.key),
+        ]);
+        let instructions = Line::from(hints).centered();
+        // narrow terminals only get the pointer to the help overlay
+        let short_instructions = Line::from(vec![" Help ".into(), "<?> ".set_style(self.theme.key)]).centered();
+
+        // alternate the alert and plain border while the finish alert is running
+        let flash_on = !self.alert_remaining.is_zero()
+            && (self.alert_remaining.as_millis() / ALERT_BLINK.as_millis()).is_multiple_of(2);
+        let border_style = if flash_on { self.theme.alert } else { self.theme.border };
 
-        let block = Block::default()
+        Block::default()
             .borders(Borders::ALL)
-            .title(title)
-            .title_bottom(instructions);
-        
-        Paragraph::default()
-            .block(block)
-            .render(area, buf);
+            .border_style(border_style)
+            .title(self.title())
+            .title_bottom(if instructions.width() as u16 + 2 <= width { instructions } else { short_instructions })
+    }
+}
+
+// the parts drawn over the framed clock: corner time, buttons, status row and popups
+impl Widget for &App {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let compact = self.is_compact(area);
+        let inner = if compact { area } else { self.frame_block(area.width).inner(area) };
+        let title_width = self.title().width() as u16;
+
+        // time of day in the top-right corner of the border, dropped if it would reach the title
+        let now = Local::now().format(" Now: %H:%M:%S ").to_string();
+        let now_width = now.len() as u16;
+        if !compact && area.width >= title_width + 2 * (now_width + 2) {
+            let corner = Rect::new(area.right() - now_width - 2, area.y, now_width, 1);
+            Paragraph::new(now.set_style(self.theme.muted)).render(corner, buf);
+        }
+
+        Paragraph::new(START_BUTTON.reversed()).render(self.start_button, buf);
+        Paragraph::new(LAP_BUTTON.reversed()).render(self.lap_button, buf);
+
+        // an open prompt takes the status row, a compact view only gives it up for a prompt
+        if inner.height > 0 && !(compact && self.input.is_none()) {
+            let row = Rect { y: inner.y + inner.height - 1, height: 1, ..inner };
+            if let Some(input) = &self.input {
+                Clear.render(row, buf);
+                Paragraph::new(Line::from(vec![input.purpose.prompt().bold(), format!("{}_", input.buffer).into()]))
+                    .centered()
+                    .render(row, buf);
+            } else if let Some((message, _)) = &self.status {
+                Paragraph::new(message.as_str().set_style(self.theme.status))
+                    .centered()
+                    .render(row, buf);
+            }
+        }
+
+        if self.show_help {
+            render_help(area, buf, &self.keybinds, &self.theme, self.help_scroll);
+        }
+
+        if self.confirm_quit {
+            let popup = centered_rect(area, 32, 3);
+            Clear.render(popup, buf);
+            Paragraph::new(Line::from(vec!["Quit without saving? ".bold(), "(".into(), "y".set_style(self.theme.key), "/".into(), "n".set_style(self.theme.key), ")".into()]))
+                .centered()
+                .block(Block::default().borders(Borders::ALL))
+                .render(popup, buf);
+        }
+    }
+}
+
+// key and action pairs listed in the help popup after the remappable ones
+const HELP_KEYS: &[(&str, &str)] = &[
+    ("s", "Record a split, the lap keeps running"),
+    ("u/Bksp", "Delete the last lap"),
+    ("w", "Export laps to laps.csv"),
+    ("e", "Export the session to session-<time>.json"),
+    ("E", "Export laps in ms to laps-<time>.csv"),
+    ("F2", "Save the screen as text to screen-<time>.txt"),
+    ("Up/Down", "Move the lap cursor (also k/j)"),
+    ("^U/^D", "Scroll the lap list half a page"),
+    ("gg/G", "Jump to the newest / oldest lap"),
+    ("Esc", "Hide the lap cursor"),
+    ("m", "Toggle stopwatch / countdown"),
+    ("t", "Cycle time format"),
+    ("p", "Cycle time precision"),
+    ("y", "Copy the current time"),
+    ("+/-", "Nudge the paused clock"),
+    ("c", "Rewind the paused clock to the last lap"),
+    ("z", "Freeze the display, the clock keeps running"),
+    ("S/O", "Save or load the clock"),
+    ("n", "Rename the session"),
+    ("L", "Label the last lap"),
+    ("b", "Toggle the bell"),
+    ("T", "Cycle color theme"),
+    ("f", "Cycle the clock font"),
+    ("v", "Fraction inline, below the time or hidden"),
+    ("D", "Set lap distance for pace"),
+    ("Tab", "Next clock (Shift+Tab previous)"),
+    ("a", "Add a clock"),
+    ("d", "Delete the lap under the cursor, or else the clock"),
+    (":", "Command line: set, countdown, clearlaps, export"),
+    ("Up/Down", "Scroll this help"),
+    ("any key", "Close this help"),
+];
+
+fn help_lines(keybinds: &Keybinds, theme: &Theme) -> Vec<Line<'static>> {
+    let remappable = [
+        (key_label(keybinds.start_pause), "Start / pause"),
+        (key_label(keybinds.lap), "Record a lap"),
+        (key_label(keybinds.reset), "Reset clock and laps"),
+        (key_label(keybinds.quit), "Quit"),
+    ];
+    let fixed = HELP_KEYS.iter().map(|(key, action)| (key.to_string(), *action));
+    remappable.into_iter().chain(fixed)
+        .map(|(key, action)| Line::from(vec![format!("{key:>7}").set_style(theme.key), format!("  {action}").into()]))
+        .collect()
+}
+
+// shrinks to the terminal and scrolls from `scroll` when the list doesn't fit
+fn render_help(area: Rect, buf: &mut Buffer, keybinds: &Keybinds, theme: &Theme, scroll: usize) {
+    let lines = help_lines(keybinds, theme);
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let popup = centered_rect(area, width, lines.len() as u16 + 2);
+    let visible = popup.height.saturating_sub(2) as usize;
+    let hidden_below = lines.len().saturating_sub(scroll + visible);
+    let mut block = Block::default().borders(Borders::ALL).title(Line::from(" Help ".bold()).centered());
+    if scroll > 0 {
+        block = block.title_top(Line::from(format!(" ▲ {scroll} ").set_style(theme.muted)).right_aligned());
+    }
+    if hidden_below > 0 {
+        block = block.title_bottom(Line::from(format!(" ▼ {hidden_below} ").set_style(theme.muted)).right_aligned());
+    }
+    Clear.render(popup, buf);
+    Paragraph::new(lines)
+        .scroll((scroll as u16, 0))
+        .block(block)
+        .render(popup, buf);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputPurpose {
+    Label,
+    LapLabel,
+    LapDistance,
+    Command,
+    Save,
+    Load,
+}
+
+impl InputPurpose {
+    fn prompt(self) -> &'static str {
+        match self {
+            InputPurpose::Label => "Label: ",
+            InputPurpose::LapLabel => "Lap label: ",
+            InputPurpose::LapDistance => "Lap distance (m): ",
+            InputPurpose::Command => ":",
+            InputPurpose::Save => "Save to: ",
+            InputPurpose::Load => "Load from: ",
+        }
     }
 }
 
+// a single-line prompt; Enter applies the buffer, Esc discards it
 #[derive(Debug)]
-struct Clockwatch {
-    running: bool,
-    elapsed_time: Duration, // accum time
-    laps: Vec<Duration>, // laps in seconds 
+struct TextInput {
+    purpose: InputPurpose,
+    buffer: String,
+}
+
+// keys that can be remapped in ~/.clockwatch/keybinds.toml, e.g. `lap = "x"`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct Keybinds {
+    start_pause: char,
+    lap: char,
+    reset: char,
+    quit: char,
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Keybinds { start_pause: ' ', lap: 'l', reset: 'r', quit: 'q' }
+    }
+}
+
+impl Keybinds {
+    fn binds(&self, c: char) -> bool {
+        [self.start_pause, self.lap, self.reset, self.quit].contains(&c)
+    }
+
+    // a missing or malformed file falls back to the defaults
+    fn load() -> Self {
+        BaseDirs::new()
+            .map(|dirs| dirs.home_dir().join(".clockwatch").join("keybinds.toml"))
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+}
+
+// every style the UI draws with, so a theme decides between color and attributes in one place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Theme {
+    border: Style,
+    title: Style,
+    key: Style, // keybinding hints
+    clock: Style, // the time itself
+    pulse: Style, // the time just after a milestone
+    running: Style,
+    paused: Style,
+    fastest: Style, // fastest lap
+    slowest: Style, // slowest lap
+    selected: Style, // lap under the cursor
+    alert: Style, // finished countdown and its flashing border
+    status: Style, // status messages
+    muted: Style, // corner time, summary and scroll hints
+    work: Style, // work phase label
+    rest: Style, // break phase label
+    gauge_high: Style, // more than half left
+    gauge_mid: Style,
+    gauge_low: Style, // under 10% left
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeName::Default.theme()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ThemeName {
+    #[default]
+    #[value(alias = "dark")]
+    Default, // the original look
+    Light,
+    Solarized,
+    Mono, // modifiers only, for terminals without color
+}
+
+// ~/.clockwatch/theme.toml remembers the preset and can recolor any part of it, e.g.
+//   name = "light"
+//   key = "cyan"
+// each color replaces that style's foreground and keeps its bold, reverse and so on
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct ThemeConfig {
+    name: ThemeName,
+    border: Option<Color>,
+    title: Option<Color>,
+    key: Option<Color>,
+    clock: Option<Color>,
+    pulse: Option<Color>,
+    running: Option<Color>,
+    paused: Option<Color>,
+    fastest: Option<Color>,
+    slowest: Option<Color>,
+    selected: Option<Color>,
+    alert: Option<Color>,
+    status: Option<Color>,
+    muted: Option<Color>,
+    work: Option<Color>,
+    rest: Option<Color>,
+    gauge_high: Option<Color>,
+    gauge_mid: Option<Color>,
+    gauge_low: Option<Color>,
+}
+
+fn theme_path() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.home_dir().join(".clockwatch").join("theme.toml"))
+}
+
+impl ThemeConfig {
+    // a missing or malformed file falls back to the default
+    fn load() -> Self {
+        theme_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    // the overrides are written back too, so cycling presets doesn't lose them
+    fn save(&self) -> io::Result<()> {
+        let path = theme_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+
+    // mono is for terminals without color, so the overrides leave it alone
+    fn theme(&self) -> Theme {
+        let mut theme = self.name.theme();
+        if self.name == ThemeName::Mono {
+            return theme;
+        }
+        let overrides = [
+            (&mut theme.border, self.border),
+            (&mut theme.title, self.title),
+            (&mut theme.key, self.key),
+            (&mut theme.clock, self.clock),
+            (&mut theme.pulse, self.pulse),
+            (&mut theme.running, self.running),
+            (&mut theme.paused, self.paused),
+            (&mut theme.fastest, self.fastest),
+            (&mut theme.slowest, self.slowest),
+            (&mut theme.selected, self.selected),
+            (&mut theme.alert, self.alert),
+            (&mut theme.status, self.status),
+            (&mut theme.muted, self.muted),
+            (&mut theme.work, self.work),
+            (&mut theme.rest, self.rest),
+            (&mut theme.gauge_high, self.gauge_high),
+            (&mut theme.gauge_mid, self.gauge_mid),
+            (&mut theme.gauge_low, self.gauge_low),
+        ];
+        for (style, color) in overrides {
+            if let Some(color) = color {
+                *style = style.fg(color);
+            }
+        }
+        theme
+    }
 }
 
-impl Clockwatch {
-    fn update(&mut self, dt: Duration) {
-        if self.running {
-            self.elapsed_time += dt;
+impl ThemeName {
+    fn next(self) -> Self {
+        match self {
+            ThemeName::Default => ThemeName::Light,
+            ThemeName::Light => ThemeName::Solarized,
+            ThemeName::Solarized => ThemeName::Mono,
+            ThemeName::Mono => ThemeName::Default,
         }
     }
 
-    fn toggle_start_pause(&mut self) {
-        self.running = !self.running;
+    fn label(self) -> &'static str {
+        match self {
+            ThemeName::Default => "default",
+            ThemeName::Light => "light",
+            ThemeName::Solarized => "solarized",
+            ThemeName::Mono => "mono",
+        }
     }
 
-    fn lap(&mut self) {
-        self.laps.push(self.elapsed_time);
+    fn theme(self) -> Theme {
+        let plain = Style::new();
+        match self {
+            ThemeName::Default => Theme { border: plain, title: plain.bold(), key: plain.blue().bold(), clock: plain, pulse: plain.cyan().bold(), running: plain.green().bold(), paused: plain.yellow().bold(), fastest: plain.green(), slowest: plain.red(), selected: plain.reversed(), alert: plain.red(), status: plain.yellow(), muted: plain.dark_gray(), work: plain.red().bold(), rest: plain.green().bold(), gauge_high: plain.green(), gauge_mid: plain.yellow(), gauge_low: plain.red() },
+            ThemeName::Light => Theme { border: plain.dark_gray(), title: plain.black().bold(), key: plain.blue().bold(), clock: plain.black(), pulse: plain.blue().bold(), running: plain.green().bold(), paused: plain.magenta().bold(), fastest: plain.green(), slowest: plain.red(), selected: plain.reversed(), alert: plain.red(), status: plain.magenta(), muted: plain.dark_gray(), work: plain.red().bold(), rest: plain.green().bold(), gauge_high: plain.green(), gauge_mid: plain.yellow(), gauge_low: plain.red() },
+            ThemeName::Solarized => Theme {
+                border: plain.fg(Color::Rgb(0x58, 0x6e, 0x75)),
+                title: plain.fg(Color::Rgb(0xb5, 0x89, 0x00)).bold(),
+                key: plain.fg(Color::Rgb(0x26, 0x8b, 0xd2)).bold(),
+                clock: plain.fg(Color::Rgb(0x2a, 0xa1, 0x98)),
+                pulse: plain.fg(Color::Rgb(0xd3, 0x36, 0x82)).bold(),
+                running: plain.fg(Color::Rgb(0x85, 0x99, 0x00)).bold(),
+                paused: plain.fg(Color::Rgb(0xb5, 0x89, 0x00)).bold(),
+                fastest: plain.fg(Color::Rgb(0x85, 0x99, 0x00)),
+                slowest: plain.fg(Color::Rgb(0xdc, 0x32, 0x2f)),
+                selected: plain.reversed(),
+                alert: plain.fg(Color::Rgb(0xdc, 0x32, 0x2f)),
+                status: plain.fg(Color::Rgb(0xb5, 0x89, 0x00)),
+                muted: plain.fg(Color::Rgb(0x58, 0x6e, 0x75)),
+                work: plain.fg(Color::Rgb(0xdc, 0x32, 0x2f)).bold(),
+                rest: plain.fg(Color::Rgb(0x85, 0x99, 0x00)).bold(),
+                gauge_high: plain.fg(Color::Rgb(0x85, 0x99, 0x00)),
+                gauge_mid: plain.fg(Color::Rgb(0xb5, 0x89, 0x00)),
+                gauge_low: plain.fg(Color::Rgb(0xdc, 0x32, 0x2f)),
+            },
+            ThemeName::Mono => Theme {
+                border: plain,
+                title: plain.bold(),
+                key: plain.bold(),
+                clock: plain,
+                pulse: plain.bold(),
+                running: plain.bold(),
+                paused: plain.dim(),
+                fastest: plain.bold(),
+                slowest: plain.underlined(),
+                selected: plain.reversed(),
+                alert: plain.reversed(),
+                status: plain.italic(),
+                muted: plain.dim(),
+                work: plain.bold(),
+                rest: plain.underlined(),
+                gauge_high: plain,
+                gauge_mid: plain,
+                gauge_low: plain.bold(),
+            },
+        }
+    }
+}
+
+fn key_label(key: char) -> String {
+    if key == ' ' { "Space".to_string() } else { key.to_string() }
+}
+
+const START_BUTTON: &str = " Start/Pause ";
+const LAP_BUTTON: &str = " Lap ";
+
+// start/pause and lap buttons centered on the row above the status line
+fn button_areas(inner: Rect) -> (Rect, Rect) {
+    if inner.height < 2 {
+        return (Rect::default(), Rect::default());
+    }
+    let gap = 2;
+    let start_width = START_BUTTON.len() as u16;
+    let lap_width = LAP_BUTTON.len() as u16;
+    let total = start_width + gap + lap_width;
+    if total > inner.width {
+        return (Rect::default(), Rect::default());
+    }
+    let x = inner.x + (inner.width - total) / 2;
+    let y = inner.y + inner.height - 2;
+    (Rect::new(x, y, start_width, 1), Rect::new(x + start_width + gap, y, lap_width, 1))
+}
+
+// the bit of arboard's Clipboard that copy_text needs, so tests can swap in a fake
+trait ClipboardSink {
+    fn set_text(&mut self, text: String) -> Result<(), arboard::Error>;
+}
+
+impl ClipboardSink for Clipboard {
+    fn set_text(&mut self, text: String) -> Result<(), arboard::Error> {
+        Clipboard::set_text(self, text)
     }
+}
+
+// returns the status message, a missing backend is reported instead of failing
+fn copy_text<C: ClipboardSink>(clipboard: Result<C, arboard::Error>, text: String) -> String {
+    match clipboard.and_then(|mut clipboard| clipboard.set_text(text)) {
+        Ok(()) => "Copied!".to_string(),
+        Err(arboard::Error::ClipboardNotSupported) => "Copy unsupported: no clipboard available".to_string(),
+        Err(err) => format!("Copy failed: {err}"),
+    }
+}
 
-    fn duration_into_text(dt: Duration) -> String {
-        let all_millis = dt.as_millis();
-        let hours: u128 = all_millis / 1000 / 60 / 60;
-        let minutes: u128 = all_millis / 1000 / 60 % 60;
-        let secs: u128 = all_millis / 1000 % 60;
-        let millis: u128 = all_millis % 1000;
-        format!("{:02}:{:02}:{:02}:{:03}", hours, minutes, secs, millis)
+// rect of at most `width` x `height` centered inside `area`
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
     }
 }
 
-impl Widget for &Clockwatch {
+// clock state saved between runs
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    elapsed_time: Duration,
+    running: bool,
+    laps: Vec<Lap>,
+    #[serde(default)]
+    dropped_laps: usize, // laps before the first saved one, for numbering
+}
+
+impl Session {
+    fn from_clock(clock: &Clockwatch) -> Self {
+        Session { elapsed_time: clock.elapsed(), running: clock.running(), laps: clock.laps().iter().cloned().collect(), dropped_laps: clock.dropped_laps() }
+    }
+
+    fn into_clock(self) -> Clockwatch {
+        Clockwatch::resume(self.elapsed_time, self.running, self.laps, self.dropped_laps)
+    }
+}
+
+fn session_path() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.home_dir().join(".clockwatch").join("session.json"))
+}
+
+// a missing file is NotFound, a corrupt one is InvalidData; callers start fresh on either
+fn load_session() -> io::Result<Vec<Clockwatch>> {
+    let path = session_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    let sessions: Vec<Session> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(sessions.into_iter().map(Session::into_clock).collect())
+}
+
+// a clock drawn with the app's theme
+struct ClockView<'a> {
+    clock: &'a Clockwatch,
+    theme: &'a Theme,
+    timestamps: bool, // show the wall-clock column
+    font: ClockFont, // font for the time
+    fraction: FractionDisplay, // inline, on its own row below or left out
+    compact: bool, // just the time filling the area
+    frozen: Option<Duration>, // shown instead of the live time
+    blink: bool, // blink the colons while running
+    lap_distance: Option<f64>, // meters per lap, adds a pace column when set
+    max_laps: Option<usize>, // most laps listed at once
+    selected: Option<usize>, // lap index under the cursor, kept in view and highlighted
+    pulse: bool, // a milestone was just passed
+}
+
+impl Widget for ClockView<'_> {
     fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        StatefulWidget::render(self, area, buf, &mut LapScroll::default());
+    }
+}
 
-        let clock_text = Text::from(vec![Line::from(Clockwatch::duration_into_text(self.elapsed_time))]);
+// where the lap list is scrolled to; only j/k, ^D/^U and gg/G move it, so h and l stay free
+// and l keeps recording laps
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct LapScroll {
+    offset: usize, // laps hidden above the visible window, newest first
+    visible: usize, // laps that fit on the last draw, sets the half-page step
+}
+
+// the offset is clamped to the visible window while rendering
+impl StatefulWidget for ClockView<'_> {
+    type State = LapScroll;
 
-        let mut laps_text = Text::from(vec![Line::from("Laps:")]);
-        for lap in &self.laps.iter().rev().collect::<Vec<&Duration>>() {
-            laps_text.push_line(Line::from(Clockwatch::duration_into_text(**lap)));
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer, lap_scroll: &mut LapScroll) {
+        let ClockView { clock, theme, timestamps, font, fraction, compact, frozen, blink, lap_distance, max_laps, selected, pulse } = self;
+
+        let (clock_text, fraction_text) = if clock.mode() == ClockMode::WallClock {
+            (Local::now().format("%H:%M:%S").to_string(), String::new())
+        } else if clock.finished() && frozen.is_none() {
+            ("DONE".to_string(), String::new())
+        } else {
+            let time = frozen.unwrap_or_else(|| clock.display_time());
+            let text = Clockwatch::duration_into_text(time, clock.format, clock.precision);
+            // the fraction is whatever follows the whole seconds, e.g. ":123" or ".12"
+            let whole = Clockwatch::duration_into_text(time, clock.format, Precision::Seconds);
+            match fraction {
+                FractionDisplay::Inline => (text, String::new()),
+                FractionDisplay::Below => (whole.clone(), text.strip_prefix(whole.as_str()).unwrap_or_default().to_string()),
+                FractionDisplay::Hidden => (whole, String::new()),
+            }
+        };
+        // colons drop out for the second half of every elapsed second, so the beat lines up with the digits;
+        // a frozen reading holds still
+        let clock_text = if blink && frozen.is_none() && clock.running() && clock.elapsed().subsec_millis() >= 500 {
+            clock_text.replace(':', " ")
+        } else {
+            clock_text
+        };
+        let clock_style = if clock.alarm_active() && clock.alarm_blink_on() {
+            theme.alert
+        } else if pulse {
+            theme.pulse
+        } else {
+            theme.clock
+        };
+
+        // the compact and one-line layouts have no row to spare for the fraction
+        if compact {
+            render_clock_text(&clock_text, font, buf, area, clock_style);
+            return;
+        }
+
+        // too short for the full layout, so just the time on one line
+        if area.height < MIN_HEIGHT {
+            render_clock_text(&clock_text, ClockFont::Plain, buf, area, clock_style);
+            return;
         }
 
+        // only laps are compared, once there are two of them; ties go to the first occurrence
+        let laps = || clock.laps().iter().enumerate().filter(|(_, lap)| !lap.split);
+        let (fastest, slowest) = if laps().nth(1).is_some() {
+            let fastest = laps().min_by_key(|(_, lap)| lap.delta).map(|(i, _)| i);
+            let slowest = laps().max_by(|(a, x), (b, y)| x.delta.cmp(&y.delta).then(b.cmp(a))).map(|(i, _)| i);
+            (fastest, slowest)
+        } else {
+            (None, None)
+        };
+
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Percentage(30),
+                Constraint::Percentage(15),
+                Constraint::Length(font.height()),
+                Constraint::Length(u16::from(!fraction_text.is_empty())),
                 Constraint::Length(1),
+                // the gauge row collapses without a countdown so the laps get the space
+                Constraint::Length(if clock.phase_target().is_zero() { 0 } else { 1 }),
                 Constraint::Min(0),
+                Constraint::Length(2),
             ]).split(area);
 
-        Paragraph::new(clock_text)
+        // rows left for laps below the two header lines, two of them go to the scroll indicators on overflow;
+        // --max-laps caps the list further
+        let rows = (layout[5].height as usize).saturating_sub(2);
+        let cap = max_laps.unwrap_or(usize::MAX);
+        let overflow = clock.laps().len() > rows.min(cap);
+        let visible = if overflow { rows.saturating_sub(2).min(cap) } else { rows };
+        // scroll just far enough to keep the cursor row on screen
+        if let Some(row) = selected.and_then(|index| clock.laps().len().checked_sub(index + 1)) {
+            lap_scroll.offset = lap_scroll.offset.min(row).max((row + 1).saturating_sub(visible));
+        }
+        lap_scroll.offset = lap_scroll.offset.min(clock.laps().len().saturating_sub(visible));
+        lap_scroll.visible = visible;
+        let hidden_below = clock.laps().len().saturating_sub(lap_scroll.offset + visible);
+
+        // the last lap is the largest time so its text is the widest column entry
+        let width = clock.laps().back().map_or(0, |lap| Clockwatch::duration_into_text(lap.at, clock.format, clock.precision).len());
+        let label_width = clock.laps().iter().filter_map(|lap| lap.label.as_ref()).map(|label| label.chars().count() + 2).max().unwrap_or(0);
+
+        // two header lines, the visible laps and both scroll indicators at most
+        let mut lines = Vec::with_capacity(visible + 4);
+        lines.push(Line::from(format!("Current lap: {}", Clockwatch::duration_into_text(clock.current_lap_time(), clock.format, clock.precision))));
+        lines.push(Line::from("Laps:"));
+        let mut laps_text = Text::from(lines);
+        if overflow {
+            laps_text.push_line(scroll_indicator("▲", lap_scroll.offset, theme));
+        }
+        for (i, lap) in clock.laps().iter().enumerate().rev().skip(lap_scroll.offset).take(visible) {
+            // fixed width columns so absolute and split times line up;
+            // numbers come from the recorded order so they don't shift as laps are added
+            let line = Line::from(format!(
+                "{:<10} {:>width$}  +{:>width$}{}{}{:<label_width$}",
+                format!("#{:03} {}", clock.dropped_laps() + i + 1, if lap.split { "Split" } else if lap.auto { "Auto" } else { "Lap" }),
+                Clockwatch::duration_into_text(lap.at, clock.format, clock.precision),
+                Clockwatch::duration_into_text(lap.delta, clock.format, clock.precision),
+                match lap_distance.map(|meters| pace(lap.delta, meters)) {
+                    None => String::new(),
+                    Some(Some(pace)) => format!("  {:>6} /km", format!("{}:{:02}", pace.as_secs() / 60, pace.as_secs() % 60)),
+                    Some(None) => format!("  {:>6} /km", "--"),
+                },
+                // same width either way so the columns stay aligned
+                match (timestamps, lap.taken_at) {
+                    (false, _) => String::new(),
+                    (true, Some(taken_at)) => taken_at.format("  %H:%M:%S").to_string(),
+                    (true, None) => "  --:--:--".to_string(),
+                },
+                lap.label.as_ref().map_or(String::new(), |label| format!("  {label}")),
+            ));
+            let line = if Some(i) == selected {
+                line.patch_style(theme.selected)
+            } else if Some(i) == fastest {
+                line.patch_style(theme.fastest)
+            } else if Some(i) == slowest {
+                line.patch_style(theme.slowest)
+            } else {
+                line
+            };
+            laps_text.push_line(line);
+        }
+        if overflow {
+            laps_text.push_line(scroll_indicator("▼", hidden_below, theme));
+        }
+
+        let phase_line = match clock.mode() {
+            ClockMode::Pomodoro { .. } => Some(Line::from(vec![
+                match clock.phase() {
+                    Phase::Work => "WORK".set_style(theme.work),
+                    Phase::Break => "BREAK".set_style(theme.rest),
+                },
+                format!("  Pomodoros: {}", clock.pomodoros()).into(),
+            ])),
+            ClockMode::Interval { rounds, .. } => Some(Line::from(vec![
+                match (clock.finished(), clock.phase()) {
+                    (true, _) => "FINISHED".bold(),
+                    (false, Phase::Work) => "WORK".set_style(theme.work),
+                    (false, Phase::Break) => "REST".set_style(theme.rest),
+                },
+                format!("  Round {}/{rounds}", clock.round()).bold(),
+            ])),
+            _ => None,
+        };
+        if let Some(phase_line) = phase_line
+            && layout[0].height > 0 {
+            let row = Rect { y: layout[0].y + layout[0].height - 1, height: 1, ..layout[0] };
+            Paragraph::new(phase_line)
+                .centered()
+                .render(row, buf);
+        }
+
+        render_clock_text(&clock_text, font, buf, layout[1], clock_style);
+        Paragraph::new(fraction_text.set_style(theme.muted))
             .centered()
-            .render(layout[1], buf);
+            .render(layout[2], buf);
 
+        // today's date under the time, there are no laps to show
+        if clock.mode() == ClockMode::WallClock {
+            Paragraph::new(Local::now().format("%A %d %B %Y").to_string())
+                .centered()
+                .render(layout[3], buf);
+            return;
+        }
+
+        // narrow terminals only get a colored dot
+        let narrow = layout[3].width < 12;
+        // frozen is its own state so a held reading isn't mistaken for a pause
+        let state = match (frozen.is_some(), clock.running(), narrow) {
+            (true, _, false) => "❄ FROZEN".set_style(theme.status),
+            (true, _, true) => "❄".set_style(theme.status),
+            (false, true, false) => "▶ RUNNING".set_style(theme.running),
+            (false, false, false) => "⏸ PAUSED".set_style(theme.paused),
+            (false, true, true) => "●".set_style(theme.running),
+            (false, false, true) => "●".set_style(theme.paused),
+        };
+        Paragraph::new(state)
+            .centered()
+            .render(layout[3], buf);
+
+        let target = clock.phase_target();
+        if !target.is_zero() {
+            let ratio = (clock.display_time().as_secs_f64() / target.as_secs_f64()).clamp(0.0, 1.0);
+            let style = if ratio > 0.5 {
+                theme.gauge_high
+            } else if ratio >= 0.1 {
+                theme.gauge_mid
+            } else {
+                theme.gauge_low
+            };
+            Gauge::default()
+                .ratio(ratio)
+                .label(format!("{:.0}% left", ratio * 100.0))
+                .gauge_style(style)
+                .render(layout[4].inner(Margin::new(2, 0)), buf);
+        }
+
+        // left-aligned in a centered column, so changing digits don't shift the rows sideways
+        let column = (laps_text.width() as u16).min(layout[5].width);
+        let column_area = Rect { x: layout[5].x + (layout[5].width - column) / 2, width: column, ..layout[5] };
         Paragraph::new(laps_text)
+            .render(column_area, buf);
+
+        let text = |dt| Clockwatch::duration_into_text(dt, clock.format, clock.precision);
+        let stats = clock.lap_stats();
+        // without laps there's nothing to average or rank, so those fields show dashes
+        let lap_text = |dt| if stats.count == 0 { "--".to_string() } else { text(dt) };
+        let summary = Text::from(vec![
+            Line::from(format!("Laps: {}  Avg: {}  Total: {}", stats.count, lap_text(stats.average), text(clock.elapsed()))),
+            Line::from(format!("Best: {}  Worst: {}", lap_text(stats.fastest), lap_text(stats.slowest))),
+        ]);
+        Paragraph::new(summary.set_style(theme.muted))
             .centered()
-            .render(layout[2], buf);
+            .render(layout[6], buf);
+    }
+}
+
+// splits under this are treated as mis-taps rather than a real pace
+const MIN_PACE_SPLIT: Duration = Duration::from_secs(1);
+
+// time per kilometer for a lap of `meters`, None when the split is too short to mean anything
+fn pace(split: Duration, meters: f64) -> Option<Duration> {
+    if split < MIN_PACE_SPLIT || meters <= 0.0 {
+        return None;
+    }
+    Some(split.mul_f64(1000.0 / meters))
+}
+
+fn scroll_indicator(arrow: &str, hidden: usize, theme: &Theme) -> Line<'static> {
+    if hidden == 0 {
+        Line::default()
+    } else {
+        Line::from(format!("{arrow} {hidden} more").set_style(theme.muted))
+    }
+}
+
+const MIN_HEIGHT: u16 = 8; // below this the clock renders as a single line
+
+// where the fraction of a second goes; split off, the big digits only change once a second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FractionDisplay {
+    Inline, // part of the time, as set by the precision
+    Below, // a dim row under the time
+    Hidden,
+}
+
+impl FractionDisplay {
+    fn next(self) -> Self {
+        match self {
+            FractionDisplay::Inline => FractionDisplay::Below,
+            FractionDisplay::Below => FractionDisplay::Hidden,
+            FractionDisplay::Hidden => FractionDisplay::Inline,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            FractionDisplay::Inline => "inline",
+            FractionDisplay::Below => "below",
+            FractionDisplay::Hidden => "hidden",
+        }
+    }
+}
+
+// fonts for the clock, each falls back to the next smaller one when the time doesn't fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClockFont {
+    Plain, // a single line of text
+    Blocks, // seven-segment style block digits
+    Standard, // figlet's standard font
+    Banner, // figlet's banner font
+}
+
+impl ClockFont {
+    fn next(self) -> Self {
+        match self {
+            ClockFont::Plain => ClockFont::Blocks,
+            ClockFont::Blocks => ClockFont::Standard,
+            ClockFont::Standard => ClockFont::Banner,
+            ClockFont::Banner => ClockFont::Plain,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ClockFont::Plain => "plain",
+            ClockFont::Blocks => "blocks",
+            ClockFont::Standard => "standard",
+            ClockFont::Banner => "banner",
+        }
+    }
+
+    const fn height(self) -> u16 {
+        match self {
+            ClockFont::Plain => 1,
+            ClockFont::Blocks | ClockFont::Standard => 5,
+            ClockFont::Banner => 7,
+        }
+    }
+
+    fn smaller(self) -> Option<Self> {
+        match self {
+            ClockFont::Banner => Some(ClockFont::Standard),
+            ClockFont::Standard => Some(ClockFont::Blocks),
+            ClockFont::Blocks => Some(ClockFont::Plain),
+            ClockFont::Plain => None,
+        }
+    }
+
+    // rows of `c`, every row of a glyph has the same width; the plain font has no glyphs
+    fn glyph(self, c: char) -> Option<&'static [&'static str]> {
+        match self {
+            ClockFont::Plain => None,
+            ClockFont::Blocks => block_glyph(c),
+            ClockFont::Standard => standard_glyph(c),
+            ClockFont::Banner => banner_glyph(c),
+        }
+    }
+}
+
+fn block_glyph(c: char) -> Option<&'static [&'static str]> {
+    let rows: &[&str] = match c {
+        '0' => &["███", "█ █", "█ █", "█ █", "███"],
+        '1' => &["  █", "  █", "  █", "  █", "  █"],
+        '2' => &["███", "  █", "███", "█  ", "███"],
+        '3' => &["███", "  █", "███", "  █", "███"],
+        '4' => &["█ █", "█ █", "███", "  █", "  █"],
+        '5' => &["███", "█  ", "███", "  █", "███"],
+        '6' => &["███", "█  ", "███", "█ █", "███"],
+        '7' => &["███", "  █", "  █", "  █", "  █"],
+        '8' => &["███", "█ █", "███", "█ █", "███"],
+        '9' => &["███", "█ █", "███", "  █", "███"],
+        ':' => &[" ", "▪", " ", "▪", " "],
+        '.' => &[" ", " ", " ", " ", "▪"],
+        'D' => &["██ ", "█ █", "█ █", "█ █", "██ "],
+        'O' => &["███", "█ █", "█ █", "█ █", "███"],
+        'N' => &["█ █", "███", "███", "█ █", "█ █"],
+        'E' => &["███", "█  ", "██ ", "█  ", "███"],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+// figlet's standard font without the blank descender row
+fn standard_glyph(c: char) -> Option<&'static [&'static str]> {
+    let rows: &[&str] = match c {
+        '0' => &["  ___  ", " / _ \\ ", "| | | |", "| |_| |", " \\___/ "],
+        '1' => &[" _ ", "/ |", "| |", "| |", "|_|"],
+        '2' => &[" ____  ", "|___ \\ ", "  __) |", " / __/ ", "|_____|"],
+        '3' => &[" _____ ", "|___ / ", "  |_ \\ ", " ___) |", "|____/ "],
+        '4' => &[" _  _   ", "| || |  ", "| || |_ ", "|__   _|", "   |_|  "],
+        '5' => &[" ____  ", "| ___| ", "|___ \\ ", " ___) |", "|____/ "],
+        '6' => &["  __   ", " / /_  ", "| '_ \\ ", "| (_) |", " \\___/ "],
+        '7' => &[" _____ ", "|___  |", "   / / ", "  / /  ", " /_/   "],
+        '8' => &["  ___  ", " ( _ ) ", " / _ \\ ", "| (_) |", " \\___/ "],
+        '9' => &["  ___  ", " / _ \\ ", "| (_) |", " \\__, |", "   /_/ "],
+        ':' => &["   ", " _ ", "(_)", " _ ", "(_)"],
+        '.' => &["   ", "   ", "   ", " _ ", "(_)"],
+        'D' => &[" ____  ", "|  _ \\ ", "| | | |", "| |_| |", "|____/ "],
+        'O' => &["  ___  ", " / _ \\ ", "| | | |", "| |_| |", " \\___/ "],
+        'N' => &[" _   _ ", "| \\ | |", "|  \\| |", "| |\\  |", "|_| \\_|"],
+        'E' => &[" _____ ", "| ____|", "|  _|  ", "| |___ ", "|_____|"],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+fn banner_glyph(c: char) -> Option<&'static [&'static str]> {
+    let rows: &[&str] = match c {
+        '0' => &["  ###  ", " #   # ", "#     #", "#     #", "#     #", " #   # ", "  ###  "],
+        '1' => &["  #  ", " ##  ", "# #  ", "  #  ", "  #  ", "  #  ", "#####"],
+        '2' => &[" ##### ", "#     #", "      #", " ##### ", "#      ", "#      ", "#######"],
+        '3' => &[" ##### ", "#     #", "      #", " ##### ", "      #", "#     #", " ##### "],
+        '4' => &["#      ", "#    # ", "#    # ", "#    # ", "#######", "     # ", "     # "],
+        '5' => &["#######", "#      ", "#      ", "###### ", "      #", "#     #", " ##### "],
+        '6' => &[" ##### ", "#     #", "#      ", "###### ", "#     #", "#     #", " ##### "],
+        '7' => &["#######", "#    # ", "    #  ", "   #   ", "  #    ", "  #    ", "  #    "],
+        '8' => &[" ##### ", "#     #", "#     #", " ##### ", "#     #", "#     #", " ##### "],
+        '9' => &[" ##### ", "#     #", "#     #", " ######", "      #", "#     #", " ##### "],
+        ':' => &[" ", " ", "#", " ", "#", " ", " "],
+        '.' => &[" ", " ", " ", " ", " ", " ", "#"],
+        'D' => &["###### ", "#     #", "#     #", "#     #", "#     #", "#     #", "###### "],
+        'O' => &[" ##### ", "#     #", "#     #", "#     #", "#     #", "#     #", " ##### "],
+        'N' => &["#     #", "##    #", "# #   #", "#  #  #", "#   # #", "#    ##", "#     #"],
+        'E' => &["#######", "#      ", "#      ", "#####  ", "#      ", "#      ", "#######"],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+// `text` as rows of glyphs, characters without a glyph become a blank column
+fn render_glyphs(text: &str, font: ClockFont) -> Text<'static> {
+    const BLANK: [&str; ClockFont::Banner.height() as usize] = [" "; ClockFont::Banner.height() as usize];
+    let height = font.height() as usize;
+    // digits are padded to the widest one so the centered time doesn't shift as a narrow 1 comes and goes
+    let digit_width = ('0'..='9').filter_map(|c| font.glyph(c)).map(|g| g[0].chars().count()).max().unwrap_or(0);
+    let glyphs = text.chars().map(|c| (c.is_ascii_digit(), font.glyph(c).unwrap_or(&BLANK[..height]))).collect::<Vec<_>>();
+    (0..height)
+        .map(|row| Line::from(glyphs.iter().map(|&(digit, g)| if digit { format!("{:^digit_width$}", g[row]) } else { g[row].to_string() }).collect::<Vec<_>>().join(" ")))
+        .collect()
+}
+
+fn buffer_text(buf: &Buffer) -> String {
+    let area = buf.area;
+    (area.top()..area.bottom())
+        .map(|y| (area.left()..area.right()).map(|x| buf[(x, y)].symbol()).collect::<String>().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// draws `text` centered in `area` in the first font from `font` down that fits, plain text when none does
+fn render_clock_text(text: &str, font: ClockFont, buf: &mut Buffer, area: Rect, style: Style) {
+    let mut font = Some(font);
+    while let Some(current) = font
+        && current != ClockFont::Plain {
+        let big_text = render_glyphs(text, current);
+        if big_text.width() as u16 <= area.width && current.height() <= area.height {
+            let top = area.y + (area.height - current.height()) / 2;
+            Paragraph::new(big_text)
+                .style(style)
+                .centered()
+                .render(Rect { y: top, height: current.height(), ..area }, buf);
+            return;
+        }
+        font = current.smaller();
+    }
+    let middle = Rect { y: area.y + area.height / 2, height: area.height.min(1), ..area };
+    Paragraph::new(text)
+        .style(style)
+        .centered()
+        .render(middle, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[derive(Default)]
+    struct FakeClipboard {
+        copied: Option<String>,
+    }
+
+    impl ClipboardSink for &mut FakeClipboard {
+        fn set_text(&mut self, text: String) -> Result<(), arboard::Error> {
+            self.copied = Some(text);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn copy_text_writes_to_the_clipboard() {
+        let mut clipboard = FakeClipboard::default();
+        let message = copy_text(Ok(&mut clipboard), "00:01:02:345".to_string());
+        assert_eq!(message, "Copied!");
+        assert_eq!(clipboard.copied.as_deref(), Some("00:01:02:345"));
+    }
+
+    #[test]
+    fn copy_text_without_a_backend_is_unsupported() {
+        let message = copy_text::<Clipboard>(Err(arboard::Error::ClipboardNotSupported), "00:00:00:000".to_string());
+        assert_eq!(message, "Copy unsupported: no clipboard available");
+    }
+
+    #[test]
+    fn tiny_areas_render_without_panicking() {
+        let mut clock = Clockwatch::new(ClockMode::Countdown { target: Duration::from_secs(60) });
+        clock.lap();
+        clock.split();
+        let theme = Theme::default();
+        for (width, height) in [(0, 0), (0, 10), (10, 0), (1, 1), (2, 2), (5, 3), (20, 7), (40, 8)] {
+            let area = Rect::new(0, 0, width, height);
+            let mut buf = Buffer::empty(area);
+            StatefulWidget::render(ClockView { clock: &clock, theme: &theme, timestamps: true, font: ClockFont::Banner, fraction: FractionDisplay::Inline, compact: false, frozen: None, blink: true, lap_distance: Some(400.0), max_laps: None, selected: None, pulse: false }, area, &mut buf, &mut LapScroll::default());
+            render_help(area, &mut buf, &Keybinds::default(), &theme, 0);
+        }
+    }
+
+    fn app() -> App {
+        App::new(&Cli::parse_from(["clockwatch", "--silent", "--no-title", "--no-milestones"]), vec![Clockwatch::new(ClockMode::Stopwatch)], ThemeConfig::default(), Keybinds::default(), None)
+    }
+
+    #[test]
+    fn loading_a_clock_drops_the_selection_and_frozen_display() {
+        let path = env::temp_dir().join(format!("clockwatch-load-{}.json", std::process::id()));
+        Clockwatch::new(ClockMode::Stopwatch).save_to(&path).unwrap();
+        let mut app = app();
+        for _ in 0..3 {
+            app.apply(Command::Lap);
+        }
+        app.select_row(0);
+        app.apply(Command::ToggleFreeze);
+        assert!(app.selected_lap.is_some() && app.frozen_display.is_some());
+        app.input = Some(TextInput { purpose: InputPurpose::Load, buffer: path.display().to_string() });
+        app.handle_input_key(KeyCode::Enter);
+        fs::remove_file(&path).unwrap();
+        assert!(app.clock().laps().is_empty());
+        assert_eq!(app.selected_lap, None);
+        assert_eq!(app.frozen_display, None);
+    }
+
+    #[test]
+    fn clicks_are_ignored_while_a_prompt_is_open() {
+        let mut app = app();
+        app.start_button = Rect::new(0, 0, 10, 1);
+        let click = MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column: 1, row: 0, modifiers: KeyModifiers::NONE };
+        app.confirm_quit = true;
+        app.handle_mouse_event(click);
+        assert!(!app.clock().running());
+
+        app.confirm_quit = false;
+        app.input = Some(TextInput { purpose: InputPurpose::Label, buffer: String::new() });
+        app.handle_mouse_event(click);
+        assert!(!app.clock().running());
+
+        app.input = None;
+        app.handle_mouse_event(click);
+        assert!(app.clock().running());
+    }
+
+    #[test]
+    fn theme_file_recolors_one_part_of_the_preset() {
+        let config = toml::from_str::<ThemeConfig>("name = \"light\"\nkey = \"cyan\"\n").unwrap();
+        let light = ThemeName::Light.theme();
+        // only the foreground changes, the preset's bold stays
+        assert_eq!(config.theme(), Theme { key: Style::new().cyan().bold(), ..light });
+        assert_eq!(ThemeConfig { name: ThemeName::Mono, ..config }.theme(), ThemeName::Mono.theme());
+        // cycling saves the whole config, so the override survives a round trip
+        assert_eq!(toml::from_str::<ThemeConfig>(&toml::to_string(&config).unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn mono_theme_draws_without_color() {
+        let mut app = app();
+        app.theme_config.name = ThemeName::Mono;
+        app.theme = app.theme_config.theme();
+        app.clocks[0] = Clockwatch::new(ClockMode::Pomodoro { work: Duration::from_secs(60), rest: Duration::from_secs(30) });
+        app.apply(Command::StartPause);
+        app.apply(Command::Lap);
+        app.apply(Command::Lap);
+        app.status = Some(("Saved".into(), Instant::now()));
+        app.alert_remaining = ALERT_BLINK * 2;
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buf = terminal.backend().buffer();
+        assert!(buf.content().iter().all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+        // state still shows up through attributes
+        assert!(buf.content().iter().any(|cell| cell.modifier.contains(ratatui::style::Modifier::REVERSED)));
+    }
+
+    #[test]
+    fn no_color_picks_mono_unless_a_theme_is_given() {
+        let cli = |args: &[&str]| Cli::try_parse_from([&["clockwatch"], args].concat()).unwrap();
+        assert_eq!(cli(&[]).theme_name(false), None);
+        assert_eq!(cli(&[]).theme_name(true), Some(ThemeName::Mono));
+        assert_eq!(cli(&["--mono"]).theme_name(false), Some(ThemeName::Mono));
+        assert_eq!(cli(&["--theme", "light"]).theme_name(true), Some(ThemeName::Light));
+        assert!(Cli::try_parse_from(["clockwatch", "--mono", "--theme", "light"]).is_err());
+    }
+
+    #[test]
+    fn version_and_help_exit_before_the_tui() {
+        let kind = |flag| Cli::try_parse_from(["clockwatch", flag]).unwrap_err().kind();
+        assert_eq!(kind("--version"), clap::error::ErrorKind::DisplayVersion);
+        assert_eq!(kind("--help"), clap::error::ErrorKind::DisplayHelp);
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn keys_map_to_commands() {
+        let keybinds = Keybinds::default();
+        let key = |code| key_to_command(&keybinds, KeyEvent::from(code));
+        assert_eq!(key(KeyCode::Char(' ')), Some(Command::StartPause));
+        assert_eq!(key(KeyCode::Char('l')), Some(Command::Lap));
+        assert_eq!(key(KeyCode::Char('-')), Some(Command::Nudge { forward: false }));
+        assert_eq!(key(KeyCode::Char('Z')), None);
+        let chord = |c, modifiers| key_to_command(&keybinds, KeyEvent::new(KeyCode::Char(c), modifiers));
+        assert_eq!(chord('r', KeyModifiers::CONTROL), None);
+        assert_eq!(chord('c', KeyModifiers::CONTROL), None);
+        assert_eq!(chord('l', KeyModifiers::ALT), None);
+        assert_eq!(chord('d', KeyModifiers::CONTROL), Some(Command::ScrollHalfPage { down: true }));
+    }
+
+    #[test]
+    fn a_keybind_on_g_beats_the_gg_prefix() {
+        let mut app = app();
+        app.keybinds.lap = 'g';
+        app.handle_key_pressed_event(KeyEvent::from(KeyCode::Char('g'))).unwrap();
+        assert_eq!(app.clock().laps().len(), 1);
+        assert!(!app.pending_g);
+    }
+
+    #[test]
+    fn vim_motions_move_the_lap_cursor() {
+        let mut app = app();
+        for _ in 0..20 {
+            app.apply(Command::Lap);
+        }
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        let mut press = |app: &mut App, code, modifiers| {
+            app.handle_key_pressed_event(KeyEvent::new(code, modifiers)).unwrap();
+            terminal.draw(|frame| app.draw(frame)).unwrap();
+            app.lap_scroll.offset
+        };
+        // the first j lands on the newest lap, the next moves down one
+        assert_eq!(press(&mut app, KeyCode::Char('j'), KeyModifiers::NONE), 0);
+        assert_eq!(app.selected_lap, Some(19));
+        press(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(app.selected_lap, Some(18));
+        let bottom = press(&mut app, KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(bottom, 20 - app.lap_scroll.visible);
+        assert_eq!(app.selected_lap, Some(0));
+        let half = app.lap_scroll.visible / 2;
+        assert_eq!(press(&mut app, KeyCode::Char('u'), KeyModifiers::CONTROL), bottom - half);
+        assert_eq!(app.clock().laps().len(), 20);
+        // a lone g waits, the second one jumps to the top
+        assert_eq!(press(&mut app, KeyCode::Char('g'), KeyModifiers::NONE), bottom - half);
+        assert_eq!(press(&mut app, KeyCode::Char('g'), KeyModifiers::NONE), 0);
+        assert_eq!(press(&mut app, KeyCode::Char('d'), KeyModifiers::CONTROL), half);
+        assert_eq!(app.selected_lap, Some(19 - half));
+        assert_eq!(app.clocks.len(), 1);
+    }
+
+    #[test]
+    fn d_deletes_the_lap_under_the_cursor() {
+        let mut app = app();
+        app.apply(Command::AddClock);
+        for _ in 0..3 {
+            app.apply(Command::Nudge { forward: true });
+            app.apply(Command::Lap);
+        }
+        let key = |app: &mut App, code| app.handle_key_pressed_event(KeyEvent::new(code, KeyModifiers::NONE)).unwrap();
+        key(&mut app, KeyCode::Char('j'));
+        key(&mut app, KeyCode::Char('j'));
+        key(&mut app, KeyCode::Char('d'));
+        // the middle lap is gone and its time went to the newest one
+        assert_eq!(app.clock().laps().iter().map(|lap| (lap.at.as_secs(), lap.delta.as_secs())).collect::<Vec<_>>(), [(1, 1), (3, 2)]);
+        assert_eq!(app.selected_lap, Some(0));
+        key(&mut app, KeyCode::Char('d'));
+        key(&mut app, KeyCode::Char('d'));
+        assert!(app.clock().laps().is_empty());
+        assert_eq!(app.selected_lap, None);
+        assert_eq!(app.clocks.len(), 2);
+
+        // without a cursor d still removes the clock
+        key(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.clocks.len(), 1);
+    }
+
+    #[test]
+    fn window_title_shows_whole_seconds_and_the_state() {
+        let mut app = app();
+        app.clock_mut().set_elapsed(Duration::from_millis(62_345));
+        assert_eq!(app.window_title_text(), "⏸ 00:01:02 - Clockwatch");
+        app.label = "Run".to_string();
+        app.apply(Command::StartPause);
+        assert_eq!(app.window_title_text(), "▶ 00:01:02 - Run");
+    }
+
+    #[test]
+    fn run_for_stops_and_exits_at_the_limit() {
+        let mut app = app();
+        app.run_for = Some(Duration::from_secs(5));
+        app.apply(Command::StartPause);
+        app.update(Duration::ZERO);
+        assert!(!app.exit);
+
+        // a new tab doesn't take over what the run is timing
+        app.apply(Command::AddClock);
+        app.clocks[0].set_elapsed(Duration::from_secs(5));
+        app.update(Duration::ZERO);
+        assert!(app.exit);
+        assert!(!app.clocks[0].running());
+    }
+
+    #[test]
+    fn run_for_ends_with_a_shorter_countdown() {
+        let mut app = app();
+        app.run_for = Some(Duration::from_secs(60));
+        app.clocks[0] = Clockwatch::new(ClockMode::Countdown { target: Duration::from_secs(5) });
+        app.apply(Command::StartPause);
+        app.clock_mut().set_elapsed(Duration::from_secs(5));
+        app.update(Duration::ZERO);
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn snapshot_matches_the_drawn_screen() {
+        let mut app = app();
+        app.apply(Command::Nudge { forward: true });
+        app.apply(Command::Lap);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let snapshot = app.snapshot();
+        assert_eq!(snapshot, buffer_text(terminal.backend().buffer()));
+        assert_eq!(snapshot.lines().count(), 20);
+        assert!(snapshot.lines().any(|line| line.contains("#001 Lap   00:00:01:000  +00:00:01:000")));
+    }
+
+    #[test]
+    fn control_lines_drive_the_clock() {
+        let mut app = app();
+        let (lines, control) = std::sync::mpsc::channel();
+        app.control = Some(control);
+        for line in ["start", "start", "lap", "bogus"] {
+            lines.send(line.to_string()).unwrap();
+        }
+        assert!(app.drain_control());
+        assert!(app.clock().running());
+        assert_eq!(app.clock().laps().len(), 1);
+        assert!(app.status.as_ref().is_some_and(|(message, _)| message == "Unknown control command: bogus"));
+
+        lines.send("pause".to_string()).unwrap();
+        lines.send("pause".to_string()).unwrap();
+        app.drain_control();
+        assert!(!app.clock().running());
+        assert!(!app.drain_control());
+    }
+
+    #[test]
+    fn control_lines_get_past_modals_and_quit_without_asking() {
+        let mut app = app();
+        let (lines, control) = std::sync::mpsc::channel();
+        app.control = Some(control);
+        app.show_help = true;
+        app.input = Some(TextInput { purpose: InputPurpose::Save, buffer: String::new() });
+        for line in ["start", "lap"] {
+            lines.send(line.to_string()).unwrap();
+        }
+        app.drain_control();
+        assert!(app.clock().running());
+        assert_eq!(app.clock().laps().len(), 1);
+        assert!(app.show_help && app.input.is_some());
+
+        // a running clock with unsaved laps would open the prompt for a key press
+        lines.send("quit".to_string()).unwrap();
+        app.drain_control();
+        assert!(app.exit);
+        assert!(!app.confirm_quit);
+    }
+
+    #[test]
+    fn milestones_pulse_the_active_clock_then_clear() {
+        let mut app = app();
+        app.milestone_every = Some(Duration::from_secs(60));
+        app.update(Duration::ZERO);
+        app.clock_mut().set_elapsed(Duration::from_secs(185));
+        app.update(Duration::ZERO);
+        assert_eq!(app.milestone_pulse, MILESTONE_PULSE);
+        assert!(app.animating());
+        app.update(MILESTONE_PULSE);
+        assert!(app.milestone_pulse.is_zero());
+    }
+
+    #[test]
+    fn only_stopwatches_go_into_the_session_file() {
+        let mut app = app();
+        app.apply(Command::AddClock);
+        app.apply(Command::ToggleMode);
+        assert_eq!(app.sessions().len(), 1);
+        app.apply(Command::RemoveClock);
+        app.apply(Command::ToggleMode);
+        assert!(app.sessions().is_empty());
+
+        let countdown = App::new(&Cli::parse_from(["clockwatch", "--countdown", "5:00"]), vec![Clockwatch::new(ClockMode::Stopwatch)], ThemeConfig::default(), Keybinds::default(), None);
+        assert!(!countdown.persist);
+    }
+
+    #[test]
+    fn start_pause_toggles_the_active_clock() {
+        let mut app = app();
+        app.apply(Command::StartPause);
+        assert!(app.clock().running());
+        app.apply(Command::StartPause);
+        assert!(!app.clock().running());
+    }
+
+    #[test]
+    fn rapid_start_pause_keys_only_toggle_once() {
+        let mut app = app();
+        let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+        app.handle_key_pressed_event(space).unwrap();
+        app.handle_key_pressed_event(space).unwrap();
+        assert!(app.clock().running());
+
+        // laps straight after each other all count
+        let lap = KeyEvent::new(KeyCode::Char('l'), KeyModifiers::NONE);
+        app.handle_key_pressed_event(lap).unwrap();
+        app.handle_key_pressed_event(lap).unwrap();
+        assert_eq!(app.clock().laps().len(), 2);
+
+        app.last_toggle = Instant::now().checked_sub(TOGGLE_DEBOUNCE);
+        app.handle_key_pressed_event(space).unwrap();
+        assert!(!app.clock().running());
+    }
+
+    #[test]
+    fn laps_are_recorded_undone_and_reset() {
+        let mut app = app();
+        app.apply(Command::Nudge { forward: true });
+        app.apply(Command::Lap);
+        app.apply(Command::Nudge { forward: true });
+        app.apply(Command::Split);
+        app.apply(Command::Nudge { forward: true });
+        app.apply(Command::Lap);
+        let deltas = app.clock().laps().iter().map(|lap| lap.delta).collect::<Vec<_>>();
+        assert_eq!(deltas, [Duration::from_secs(1), Duration::from_secs(1), Duration::from_secs(2)]);
+
+        app.apply(Command::UndoLap);
+        assert_eq!(app.clock().laps().len(), 2);
+
+        app.apply(Command::StartPause);
+        app.apply(Command::Reset);
+        assert!(app.clock().laps().is_empty());
+        assert!(!app.clock().running());
+        assert_eq!(app.clock().elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn nudging_only_moves_a_paused_clock() {
+        let mut app = app();
+        app.apply(Command::Nudge { forward: false });
+        assert_eq!(app.clock().elapsed(), Duration::ZERO);
+        app.apply(Command::Nudge { forward: true });
+        assert_eq!(app.clock().elapsed(), Duration::from_secs(1));
+        app.apply(Command::StartPause);
+        app.apply(Command::Nudge { forward: true });
+        assert!(app.clock().elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn a_wall_clock_gap_pauses_running_clocks() {
+        let mut app = app();
+        app.apply(Command::StartPause);
+        app.pause_after_gap(Duration::from_secs(1), Duration::from_secs(1));
+        assert!(app.clock().running());
+
+        app.pause_after_gap(Duration::from_secs(600), Duration::ZERO);
+        assert!(!app.clock().running());
+        assert!(app.status.as_ref().is_some_and(|(message, _)| message == "Paused after a 600s gap"));
+
+        app.gap_threshold = None;
+        app.apply(Command::StartPause);
+        app.pause_after_gap(Duration::from_secs(600), Duration::ZERO);
+        assert!(app.clock().running());
+    }
+
+    #[test]
+    fn freezing_holds_the_display_but_not_the_clock() {
+        let mut app = app();
+        app.apply(Command::Nudge { forward: true });
+        app.apply(Command::ToggleFreeze);
+        assert_eq!(app.frozen_display, Some(Duration::from_secs(1)));
+        app.apply(Command::Nudge { forward: true });
+        assert_eq!(app.clock().elapsed(), Duration::from_secs(2));
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 12)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>();
+        assert!(screen.contains("00:00:01:000"));
+        assert!(screen.contains("FROZEN"));
+
+        app.apply(Command::ToggleFreeze);
+        assert_eq!(app.frozen_display, None);
+    }
+
+    #[test]
+    fn quit_asks_only_when_there_is_something_to_lose() {
+        let mut fresh = app();
+        fresh.apply(Command::Quit);
+        assert!(fresh.exit);
+
+        let mut used = app();
+        used.apply(Command::Lap);
+        used.apply(Command::Quit);
+        assert!(used.confirm_quit);
+        assert!(!used.exit);
+
+        // exported laps aren't lost, so there's nothing to ask about
+        let mut exported = app();
+        exported.apply(Command::Lap);
+        exported.clock_mut().mark_saved();
+        exported.apply(Command::Quit);
+        assert!(exported.exit);
+    }
+
+    #[test]
+    fn failed_writes_show_the_error_and_keep_laps_unsaved() {
+        let mut app = app();
+        app.apply(Command::Lap);
+        app.report_write(Err(io::Error::from(io::ErrorKind::PermissionDenied)), "Export", "Written".to_string());
+        assert!(app.status.as_ref().is_some_and(|(message, _)| message == "Export failed: permission denied"));
+        assert!(app.clock().unsaved());
+
+        app.report_write(Ok(()), "Export", "Written".to_string());
+        assert!(app.status.as_ref().is_some_and(|(message, _)| message == "Written"));
+        assert!(!app.clock().unsaved());
+    }
+
+    #[test]
+    fn commands_go_to_the_active_clock() {
+        let mut app = app();
+        app.apply(Command::AddClock);
+        assert_eq!(app.clocks.len(), 2);
+        assert_eq!(app.active, 1);
+        app.apply(Command::Lap);
+        app.apply(Command::PrevClock);
+        assert!(app.clock().laps().is_empty());
+        assert_eq!(app.clocks[1].laps().len(), 1);
+        app.apply(Command::RemoveClock);
+        assert_eq!(app.clocks.len(), 1);
+    }
+
+    #[test]
+    fn added_clocks_keep_the_lap_cap() {
+        let mut app = app();
+        app.clock_mut().set_keep_laps(Some(2));
+        app.apply(Command::AddClock);
+        for _ in 0..3 {
+            app.apply(Command::Lap);
+        }
+        assert_eq!(app.clock().laps().len(), 2);
+        assert_eq!(app.clock().dropped_laps(), 1);
+    }
+
+    #[test]
+    fn command_line_edits_the_active_clock() {
+        let mut app = app();
+        app.parse_command("set 1:30").unwrap();
+        assert_eq!(app.clock().elapsed(), Duration::from_secs(90));
+
+        app.apply(Command::Lap);
+        app.parse_command("clearlaps").unwrap();
+        assert!(app.clock().laps().is_empty());
+
+        app.parse_command("countdown 5m").unwrap();
+        assert_eq!(app.clock().mode(), ClockMode::Countdown { target: Duration::from_secs(300) });
+        assert_eq!(app.clock().elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn command_line_rejects_bad_input() {
+        let mut app = app();
+        assert_eq!(app.parse_command("set soon"), Err("Not a duration: soon".to_string()));
+        assert_eq!(app.parse_command("set"), Err("Usage: :set <time>".to_string()));
+        assert_eq!(app.parse_command("clearlaps now"), Err("Usage: :clearlaps".to_string()));
+        assert_eq!(app.parse_command("frobnicate"), Err("Unknown command: frobnicate".to_string()));
+    }
+
+    #[test]
+    fn lap_list_renders_newest_first_with_highlights() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.precision = Precision::Seconds;
+        for (secs, split) in [(5, false), (11, false), (13, true), (15, false)] {
+            clock.set_elapsed(Duration::from_secs(secs));
+            if split {
+                clock.split();
+            } else {
+                clock.lap();
+            }
+        }
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 40, 16);
+        let mut buf = Buffer::empty(area);
+        StatefulWidget::render(ClockView { clock: &clock, theme: &theme, timestamps: false, font: ClockFont::Plain, fraction: FractionDisplay::Inline, compact: false, frozen: None, blink: false, lap_distance: None, max_laps: None, selected: None, pulse: false }, area, &mut buf, &mut LapScroll::default());
+
+        let rows = (0..area.height).map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect::<String>()).collect::<Vec<_>>();
+        let laps = rows.iter().map(|row| row.trim()).filter(|row| row.starts_with('#')).collect::<Vec<_>>();
+        assert_eq!(laps, [
+            "#004 Lap   00:00:15  +00:00:04",
+            "#003 Split 00:00:13  +00:00:02",
+            "#002 Lap   00:00:11  +00:00:06",
+            "#001 Lap   00:00:05  +00:00:05",
+        ]);
+
+        // the fastest lap is green and the slowest red, the split is left alone
+        let style_of = |text: &str| {
+            let y = rows.iter().position(|row| row.trim() == text).unwrap() as u16;
+            let x = rows[y as usize].find(text).unwrap() as u16;
+            buf[(x, y)].fg
+        };
+        assert_eq!(style_of(laps[0]), Color::Green);
+        assert_eq!(style_of(laps[1]), Color::Reset);
+        assert_eq!(style_of(laps[2]), Color::Red);
+    }
+
+    #[test]
+    fn compact_view_draws_only_the_time() {
+        let mut app = app();
+        app.apply(Command::Lap);
+        let rows = |app: &mut App, width, height| {
+            let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+            terminal.draw(|frame| app.draw(frame)).unwrap();
+            let buf = terminal.backend().buffer().clone();
+            (0..height).map(|y| (0..width).map(|x| buf[(x, y)].symbol()).collect::<String>().trim().to_string()).filter(|row| !row.is_empty()).collect::<Vec<_>>()
+        };
+        // a single row is compact even without the flag
+        assert_eq!(rows(&mut app, 30, 1), ["00:00:00:000"]);
+        app.compact = true;
+        assert_eq!(rows(&mut app, 30, 5), ["00:00:00:000"]);
+        // keys still work, an open prompt shows on the last row
+        app.apply(Command::Rename);
+        assert_eq!(rows(&mut app, 30, 1), ["Label: _"]);
+    }
+
+    #[test]
+    fn lap_list_is_capped_and_left_aligned() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        for secs in 1..=5 {
+            clock.set_elapsed(Duration::from_secs(secs));
+            clock.lap();
+        }
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 60, 24);
+        let mut buf = Buffer::empty(area);
+        StatefulWidget::render(ClockView { clock: &clock, theme: &theme, timestamps: false, font: ClockFont::Plain, fraction: FractionDisplay::Inline, compact: false, frozen: None, blink: false, lap_distance: None, max_laps: Some(2), selected: None, pulse: false }, area, &mut buf, &mut LapScroll::default());
+
+        let rows = (0..area.height).map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect::<String>()).collect::<Vec<_>>();
+        let laps = rows.iter().filter(|row| row.trim_start().starts_with('#')).collect::<Vec<_>>();
+        assert_eq!(laps.len(), 2);
+        assert!(rows.iter().any(|row| row.trim() == "▼ 3 more"));
+        // every row of the list starts in the same column
+        let column = laps[0].find('#').unwrap();
+        assert!(rows.iter().filter(|row| row.trim() == "Laps:" || row.contains("more")).all(|row| row.find(|c: char| c != ' ') == Some(column)));
+    }
+
+    #[test]
+    fn the_fraction_can_sit_below_steady_digits() {
+        for font in [ClockFont::Blocks, ClockFont::Standard, ClockFont::Banner] {
+            assert_eq!(render_glyphs("11:11", font).width(), render_glyphs("00:00", font).width());
+        }
+
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.set_elapsed(Duration::from_millis(61_234));
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, 40, 20);
+        let rows = |fraction| {
+            let mut buf = Buffer::empty(area);
+            StatefulWidget::render(ClockView { clock: &clock, theme: &theme, timestamps: false, font: ClockFont::Plain, fraction, compact: false, frozen: None, blink: false, lap_distance: None, max_laps: None, selected: None, pulse: false }, area, &mut buf, &mut LapScroll::default());
+            (0..area.height).map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect::<String>().trim().to_string()).collect::<Vec<_>>()
+        };
+        let below = rows(FractionDisplay::Below);
+        let time = below.iter().position(|row| row == "00:01:01").unwrap();
+        assert_eq!(below[time + 1], ":234");
+        let hidden = rows(FractionDisplay::Hidden);
+        assert!(hidden.iter().any(|row| row == "00:01:01") && hidden.iter().all(|row| row != ":234"));
+        assert!(rows(FractionDisplay::Inline).iter().any(|row| row == "00:01:01:234"));
+    }
+
+    // the clock view on its own at a fixed size, without anything tied to the wall clock
+    fn render_view(clock: &Clockwatch, width: u16, height: u16) -> String {
+        let theme = Theme::default();
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        Widget::render(ClockView { clock, theme: &theme, timestamps: false, font: ClockFont::Plain, fraction: FractionDisplay::Inline, compact: false, frozen: None, blink: false, lap_distance: None, max_laps: None, selected: None, pulse: false }, area, &mut buf);
+        buffer_text(&buf)
+    }
+
+    #[test]
+    fn view_snapshot_without_laps() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        clock.set_elapsed(Duration::from_millis(83_456));
+        let expected = [
+            "",
+            "",
+            "              00:01:23:456",
+            "                ⏸ PAUSED",
+            "       Current lap: 00:01:23:456",
+            "       Laps:",
+            "",
+            "",
+            "",
+            "",
+            "  Laps: 0  Avg: --  Total: 00:01:23:456",
+            "           Best: --  Worst: --",
+        ];
+        assert_eq!(render_view(&clock, 40, 12), expected.join("\n"));
+    }
+
+    #[test]
+    fn view_snapshot_with_many_laps() {
+        let mut clock = Clockwatch::new(ClockMode::Stopwatch);
+        for (i, millis) in [4_100, 9_050, 12_900, 19_000, 21_250, 28_000, 31_500, 36_750].into_iter().enumerate() {
+            clock.set_elapsed(Duration::from_millis(millis));
+            if i == 2 { clock.split(); } else { clock.lap(); }
+        }
+        clock.label_last_lap("cooldown".to_string());
+        clock.set_elapsed(Duration::from_millis(40_000));
+        // the split doesn't count towards the summary, and the list scrolls with 4 laps below
+        let expected = [
+            "",
+            "",
+            "                  00:00:40:000",
+            "                    ⏸ PAUSED",
+            "Current lap: 00:00:03:250",
+            "Laps:",
+            "",
+            "#008 Lap   00:00:36:750  +00:00:05:250  cooldown",
+            "#007 Lap   00:00:31:500  +00:00:03:500",
+            "#006 Lap   00:00:28:000  +00:00:06:750",
+            "#005 Lap   00:00:21:250  +00:00:02:250",
+            "▼ 4 more",
+            " Laps: 7  Avg: 00:00:05:250  Total: 00:00:40:000",
+            "     Best: 00:00:02:250  Worst: 00:00:09:950",
+        ];
+        assert_eq!(render_view(&clock, 48, 14), expected.join("\n"));
+    }
+
+    #[test]
+    fn fonts_fall_back_until_the_time_fits() {
+        let drawn = |width, height| {
+            let area = Rect::new(0, 0, width, height);
+            let mut buf = Buffer::empty(area);
+            render_clock_text("00:00:00", ClockFont::Banner, &mut buf, area, Style::new());
+            buf.content().iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        assert!(drawn(60, 7).contains('#'));
+        // too short for the banner, the standard font fits
+        assert!(drawn(64, 5).contains('|'));
+        assert!(drawn(40, 7).contains('█'));
+        assert!(drawn(20, 7).contains("00:00:00"));
+    }
+
+    #[test]
+    fn durations_reject_out_of_range_fields() {
+        assert_eq!(parse_duration("1:02:03"), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_duration("90:00"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("99:99:99"), None);
+        assert_eq!(parse_duration("1:60"), None);
+        assert_eq!(parse_duration("soon"), None);
+        assert_eq!(parse_duration("9999999999999999h"), None);
+        assert_eq!(parse_duration("307445734561825861:00"), None);
+        assert_eq!(parse_duration("18446744073709551615"), None);
+        assert_eq!(parse_duration("9999:59:59"), Some(Duration::from_secs(9999 * 3600 + 3599)));
+        assert_eq!(parse_duration("10001h"), None);
     }
 }